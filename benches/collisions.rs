@@ -0,0 +1,62 @@
+//! Ticks games crowded with asteroids and lasers, to keep an eye on how the
+//! collision checks scale. Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use planet_defender::{config::GameConfig, game::Game};
+use std::f32::consts::PI;
+
+/// Ticks run from just before the lasers reach the asteroids, and the ticks
+/// measured, which span the collisions.
+const SETUP_TICKS: usize = 16;
+const MEASURED_TICKS: usize = 8;
+
+/// A game with `entities` split evenly between asteroids closing in from
+/// every side and lasers fired all the way around the planet, about to
+/// collide.
+fn crowded_game(entities: usize) -> Game {
+    let mut game = Game::with_seed(1);
+    // spawning asteroids just past the ship's nose, so lasers reach them in
+    // a few ticks
+    game.set_config(GameConfig {
+        spawn_distance: 250.0,
+        ..GameConfig::default()
+    });
+    game.start();
+    for _ in 0..entities / 2 {
+        game.add_asteroid();
+    }
+    for i in 0..entities / 2 {
+        let angle = i as f32 / (entities / 2) as f32 * 2.0 * PI;
+        game.set_cursor(
+            (500.0 + 400.0 * angle.cos()) as u32,
+            (500.0 + 400.0 * angle.sin()) as u32,
+        );
+        game.fire();
+    }
+    for _ in 0..SETUP_TICKS {
+        game.tick();
+    }
+    game
+}
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for entities in [100, 1_000, 10_000] {
+        group.bench_with_input(BenchmarkId::from_parameter(entities), &entities, |b, &n| {
+            b.iter_batched(
+                || crowded_game(n),
+                |mut game| {
+                    for _ in 0..MEASURED_TICKS {
+                        game.tick();
+                    }
+                    game
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick);
+criterion_main!(benches);
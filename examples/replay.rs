@@ -0,0 +1,35 @@
+use planet_defender::{
+    config::{GameConfig, CONFIG_PATH},
+    replay::{Replay, ReplayError},
+    waves::{Waves, WAVES_PATH},
+};
+
+// Plays a replay file back headlessly and prints how the run ended, e.g. to
+// verify a high score:
+//
+//     cargo run --example replay -- run.pdr
+//
+// Replays recorded with the files in `assets` edited are played back with
+// them, as they're only reproduced with the tuning they were recorded with.
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("usage: replay <replay file>");
+    let replay = Replay::load(&path).unwrap();
+    let game = match replay.play() {
+        Err(ReplayError::TuningMismatch) => replay.play_with(
+            GameConfig::load(CONFIG_PATH).unwrap(),
+            Waves::load(WAVES_PATH).unwrap(),
+        ),
+        result => result,
+    };
+    let snapshot = game.unwrap().snapshot();
+
+    println!("seed:      {}", replay.seed);
+    println!("ticks:     {}", snapshot.tick);
+    println!("time:      {:.2}", snapshot.game_time as f64 / 1000.0);
+    println!("destroyed: {}", snapshot.asteroids_destroyed);
+    println!("score:     {}", snapshot.score);
+    println!("lives:     {}", snapshot.lives);
+    println!("state:     {:?}", snapshot.state);
+}
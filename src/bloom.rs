@@ -0,0 +1,232 @@
+use wgpu::util::DeviceExt;
+
+use crate::fullscreen;
+
+/// Spacing between blur taps in texels of the half-resolution glow textures;
+/// larger values give a wider, softer glow.
+const BLUR_SPREAD: f32 = 1.5;
+
+/// Phosphor glow post-process: the scene is rendered into an offscreen
+/// texture, blurred at half resolution in two separable passes, then added
+/// back on top of itself when composited onto the surface.
+pub struct Bloom {
+    format: wgpu::TextureFormat,
+    blur_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    blur_layout: wgpu::BindGroupLayout,
+    composite_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    horizontal_buffer: wgpu::Buffer,
+    vertical_buffer: wgpu::Buffer,
+    // None while the surface is too large for the device's texture limits
+    targets: Option<Targets>,
+}
+
+struct Targets {
+    scene_view: wgpu::TextureView,
+    ping_view: wgpu::TextureView,
+    pong_view: wgpu::TextureView,
+    horizontal_bind_group: wgpu::BindGroup,
+    vertical_bind_group: wgpu::BindGroup,
+    composite_bind_group: wgpu::BindGroup,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BlurUniform {
+    // padded to 16 bytes for WebGL's uniform buffer rules
+    direction: [f32; 4],
+}
+
+impl Bloom {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> Self {
+        let shader = fullscreen::shader(device, "Bloom Shader", include_str!("bloom.wgsl"));
+
+        let blur_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                fullscreen::texture_entry(0),
+                fullscreen::sampler_entry(1),
+                fullscreen::uniform_entry(2),
+            ],
+            label: Some("bloom_blur_bind_group_layout"),
+        });
+        let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                fullscreen::texture_entry(0),
+                fullscreen::sampler_entry(1),
+                fullscreen::texture_entry(3),
+            ],
+            label: Some("bloom_composite_bind_group_layout"),
+        });
+
+        let pipeline = |label, layout, entry_point| {
+            fullscreen::pipeline(device, label, &shader, layout, entry_point, config.format)
+        };
+        let blur_pipeline = pipeline("Bloom Blur Pipeline", &blur_layout, "fs_blur");
+        let composite_pipeline = pipeline(
+            "Bloom Composite Pipeline",
+            &composite_layout,
+            "fs_composite",
+        );
+
+        let sampler = fullscreen::sampler(device, "Bloom Sampler");
+
+        // the directions depend on the texture size and are filled in by `resize`
+        let uniform_buffer = |label| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&[BlurUniform {
+                    direction: [0.0; 4],
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        };
+        let horizontal_buffer = uniform_buffer("Bloom Horizontal Buffer");
+        let vertical_buffer = uniform_buffer("Bloom Vertical Buffer");
+
+        let mut bloom = Self {
+            format: config.format,
+            blur_pipeline,
+            composite_pipeline,
+            blur_layout,
+            composite_layout,
+            sampler,
+            horizontal_buffer,
+            vertical_buffer,
+            targets: None,
+        };
+        bloom.resize(device, queue, config.width, config.height);
+        bloom
+    }
+
+    /// Recreates the offscreen textures for a `width` x `height` surface, or
+    /// drops them if the device can't hold textures that large.
+    pub fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32) {
+        if !fullscreen::fits(device, width, height) {
+            self.targets = None;
+            return;
+        }
+
+        let create_view = |label, width, height| {
+            fullscreen::target_view(device, label, self.format, width, height)
+        };
+        let (half_width, half_height) = ((width / 2).max(1), (height / 2).max(1));
+        let scene_view = create_view("Bloom Scene Texture", width, height);
+        let ping_view = create_view("Bloom Ping Texture", half_width, half_height);
+        let pong_view = create_view("Bloom Pong Texture", half_width, half_height);
+
+        let blur_bind_group = |label, source, buffer: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.blur_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+                label: Some(label),
+            })
+        };
+        let horizontal_bind_group = blur_bind_group(
+            "bloom_horizontal_bind_group",
+            &scene_view,
+            &self.horizontal_buffer,
+        );
+        let vertical_bind_group = blur_bind_group(
+            "bloom_vertical_bind_group",
+            &ping_view,
+            &self.vertical_buffer,
+        );
+        let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.composite_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&scene_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&pong_view),
+                },
+            ],
+            label: Some("bloom_composite_bind_group"),
+        });
+
+        self.targets = Some(Targets {
+            scene_view,
+            ping_view,
+            pong_view,
+            horizontal_bind_group,
+            vertical_bind_group,
+            composite_bind_group,
+        });
+
+        let horizontal = BlurUniform {
+            direction: [BLUR_SPREAD / half_width as f32, 0.0, 0.0, 0.0],
+        };
+        let vertical = BlurUniform {
+            direction: [0.0, BLUR_SPREAD / half_height as f32, 0.0, 0.0],
+        };
+        queue.write_buffer(
+            &self.horizontal_buffer,
+            0,
+            bytemuck::cast_slice(&[horizontal]),
+        );
+        queue.write_buffer(&self.vertical_buffer, 0, bytemuck::cast_slice(&[vertical]));
+    }
+
+    /// The view the scene should be rendered into instead of the surface, if
+    /// bloom can currently run.
+    pub fn scene_view(&self) -> Option<&wgpu::TextureView> {
+        self.targets.as_ref().map(|targets| &targets.scene_view)
+    }
+
+    /// Blurs the scene and composites it with its glow onto `target`.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let targets = match &self.targets {
+            Some(targets) => targets,
+            None => return,
+        };
+
+        let passes = [
+            (
+                "Bloom Horizontal Pass",
+                &targets.ping_view,
+                &self.blur_pipeline,
+                &targets.horizontal_bind_group,
+            ),
+            (
+                "Bloom Vertical Pass",
+                &targets.pong_view,
+                &self.blur_pipeline,
+                &targets.vertical_bind_group,
+            ),
+            (
+                "Bloom Composite Pass",
+                target,
+                &self.composite_pipeline,
+                &targets.composite_bind_group,
+            ),
+        ];
+
+        for (label, view, pipeline, bind_group) in passes {
+            fullscreen::draw(encoder, label, view, pipeline, bind_group);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Glow passes, run after the fullscreen vertex shader in fullscreen.wgsl
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var linear_sampler: sampler;
+
+// Blur pass
+
+struct BlurUniform {
+    // xy: distance between taps in uv space, zw: unused padding
+    direction: vec4<f32>,
+};
+@group(0) @binding(2)
+var<uniform> blur: BlurUniform;
+
+@fragment
+fn fs_blur(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    // 9-tap gaussian folded into 5 bilinear samples
+    let near = blur.direction.xy * 1.3846153846;
+    let far = blur.direction.xy * 3.2307692308;
+
+    var color = textureSample(source, linear_sampler, in.uv).rgb * 0.2270270270;
+    color = color + textureSample(source, linear_sampler, in.uv + near).rgb * 0.3162162162;
+    color = color + textureSample(source, linear_sampler, in.uv - near).rgb * 0.3162162162;
+    color = color + textureSample(source, linear_sampler, in.uv + far).rgb * 0.0702702703;
+    color = color + textureSample(source, linear_sampler, in.uv - far).rgb * 0.0702702703;
+    return vec4<f32>(color, 1.0);
+}
+
+// Composite pass
+
+@group(0) @binding(3)
+var glow: texture_2d<f32>;
+
+@fragment
+fn fs_composite(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let scene = textureSample(source, linear_sampler, in.uv).rgb;
+    let bloom = textureSample(glow, linear_sampler, in.uv).rgb;
+    return vec4<f32>(scene + bloom * 1.5, 1.0);
+}
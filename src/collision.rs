@@ -0,0 +1,273 @@
+//! Exact intersection tests between the shapes the game is made of: laser
+//! paths are segments, asteroids are polygons and the planet is a circle.
+//! Polygons are given as their corners in order, either winding.
+
+use crate::draw::Point;
+
+fn cross(o: Point, a: Point, b: Point) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Whether `p`, known to be collinear with `a` and `b`, lies between them.
+fn within(p: Point, a: Point, b: Point) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+/// Whether segment `a1`-`a2` touches or crosses segment `b1`-`b2`.
+pub fn segments_intersect(a1: Point, a2: Point, b1: Point, b2: Point) -> bool {
+    // segments whose boxes don't overlap can't meet, which rules out most
+    // edges of a polygon without working out any cross products
+    if a1.x.max(a2.x) < b1.x.min(b2.x)
+        || b1.x.max(b2.x) < a1.x.min(a2.x)
+        || a1.y.max(a2.y) < b1.y.min(b2.y)
+        || b1.y.max(b2.y) < a1.y.min(a2.y)
+    {
+        return false;
+    }
+
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    // an end of one segment lying on the other
+    (d1 == 0.0 && within(a1, b1, b2))
+        || (d2 == 0.0 && within(a2, b1, b2))
+        || (d3 == 0.0 && within(b1, a1, a2))
+        || (d4 == 0.0 && within(b2, a1, a2))
+}
+
+/// Whether `p` is inside `polygon`, by the even-odd rule.
+pub fn point_in_polygon(p: Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[j];
+        // count the edges a ray from `p` towards +x crosses
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Distance from `p` to the closest point of segment `a`-`b`.
+pub fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    (p.x - (a.x + t * dx)).hypot(p.y - (a.y + t * dy))
+}
+
+fn edges(polygon: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    polygon
+        .iter()
+        .copied()
+        .zip(polygon.iter().copied().cycle().skip(1))
+}
+
+/// Whether the segment from `start` to `end` touches `polygon` anywhere. Used
+/// with the distance something travelled in one tick as the segment, this
+/// catches fast objects that would otherwise pass straight through.
+pub fn segment_intersects_polygon(start: Point, end: Point, polygon: &[Point]) -> bool {
+    // a segment entirely inside crosses no edges
+    point_in_polygon(start, polygon)
+        || edges(polygon).any(|(a, b)| segments_intersect(start, end, a, b))
+}
+
+/// Whether `polygon` overlaps the circle around `center`.
+pub fn polygon_intersects_circle(polygon: &[Point], center: Point, radius: f32) -> bool {
+    // either the circle's centre is inside the polygon, or some edge comes
+    // within `radius` of it, which also covers polygons inside the circle
+    point_in_polygon(center, polygon)
+        || edges(polygon).any(|(a, b)| distance_to_segment(center, a, b) <= radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    /// 10 x 10 square with its top left corner at the origin.
+    fn square() -> Vec<Point> {
+        vec![pt(0.0, 0.0), pt(10.0, 0.0), pt(10.0, 10.0), pt(0.0, 10.0)]
+    }
+
+    #[test]
+    fn crossing_segments_intersect() {
+        assert!(segments_intersect(
+            pt(0.0, 0.0),
+            pt(10.0, 10.0),
+            pt(0.0, 10.0),
+            pt(10.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn parallel_segments_do_not_intersect() {
+        assert!(!segments_intersect(
+            pt(0.0, 0.0),
+            pt(10.0, 0.0),
+            pt(0.0, 1.0),
+            pt(10.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn touching_segments_intersect() {
+        assert!(segments_intersect(
+            pt(0.0, 0.0),
+            pt(5.0, 5.0),
+            pt(5.0, 5.0),
+            pt(10.0, 0.0)
+        ));
+        // T junction
+        assert!(segments_intersect(
+            pt(0.0, 0.0),
+            pt(10.0, 0.0),
+            pt(5.0, 0.0),
+            pt(5.0, 5.0)
+        ));
+    }
+
+    #[test]
+    fn collinear_segments() {
+        assert!(segments_intersect(
+            pt(0.0, 0.0),
+            pt(6.0, 0.0),
+            pt(4.0, 0.0),
+            pt(10.0, 0.0)
+        ));
+        assert!(!segments_intersect(
+            pt(0.0, 0.0),
+            pt(4.0, 0.0),
+            pt(6.0, 0.0),
+            pt(10.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn points_in_square() {
+        let square = square();
+        assert!(point_in_polygon(pt(5.0, 5.0), &square));
+        assert!(!point_in_polygon(pt(15.0, 5.0), &square));
+        assert!(!point_in_polygon(pt(-5.0, 5.0), &square));
+        assert!(!point_in_polygon(pt(5.0, -5.0), &square));
+    }
+
+    #[test]
+    fn point_in_concave_polygon() {
+        // U shape opening upwards
+        let u = vec![
+            pt(0.0, 0.0),
+            pt(3.0, 0.0),
+            pt(3.0, 7.0),
+            pt(7.0, 7.0),
+            pt(7.0, 0.0),
+            pt(10.0, 0.0),
+            pt(10.0, 10.0),
+            pt(0.0, 10.0),
+        ];
+        assert!(point_in_polygon(pt(1.0, 5.0), &u));
+        assert!(!point_in_polygon(pt(5.0, 5.0), &u));
+        assert!(point_in_polygon(pt(5.0, 9.0), &u));
+    }
+
+    #[test]
+    fn segment_distance() {
+        assert_eq!(
+            distance_to_segment(pt(5.0, 3.0), pt(0.0, 0.0), pt(10.0, 0.0)),
+            3.0
+        );
+        // past the end, so measured to the end point
+        assert_eq!(
+            distance_to_segment(pt(13.0, 4.0), pt(0.0, 0.0), pt(10.0, 0.0)),
+            5.0
+        );
+        // degenerate segment
+        assert_eq!(
+            distance_to_segment(pt(3.0, 4.0), pt(0.0, 0.0), pt(0.0, 0.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn swept_segment_tunnelling_through_polygon() {
+        // both ends are outside, as if a laser skipped over it in one tick
+        assert!(segment_intersects_polygon(
+            pt(-20.0, 5.0),
+            pt(30.0, 5.0),
+            &square()
+        ));
+    }
+
+    #[test]
+    fn swept_segment_inside_polygon() {
+        assert!(segment_intersects_polygon(
+            pt(2.0, 2.0),
+            pt(8.0, 8.0),
+            &square()
+        ));
+    }
+
+    #[test]
+    fn swept_segment_entering_polygon() {
+        assert!(segment_intersects_polygon(
+            pt(-5.0, 5.0),
+            pt(5.0, 5.0),
+            &square()
+        ));
+    }
+
+    #[test]
+    fn swept_segment_missing_polygon() {
+        assert!(!segment_intersects_polygon(
+            pt(-20.0, 15.0),
+            pt(30.0, 15.0),
+            &square()
+        ));
+        // would hit if it were longer
+        assert!(!segment_intersects_polygon(
+            pt(-20.0, 5.0),
+            pt(-1.0, 5.0),
+            &square()
+        ));
+    }
+
+    #[test]
+    fn circle_overlapping_edge() {
+        assert!(polygon_intersects_circle(&square(), pt(13.0, 5.0), 4.0));
+        assert!(!polygon_intersects_circle(&square(), pt(15.0, 5.0), 4.0));
+    }
+
+    #[test]
+    fn circle_near_corner() {
+        // 5 from the corner at (10, 10), but over 5 along either axis
+        assert!(polygon_intersects_circle(&square(), pt(13.0, 14.0), 5.0));
+        assert!(!polygon_intersects_circle(&square(), pt(13.0, 14.0), 4.9));
+    }
+
+    #[test]
+    fn circle_inside_polygon() {
+        assert!(polygon_intersects_circle(&square(), pt(5.0, 5.0), 1.0));
+    }
+
+    #[test]
+    fn polygon_inside_circle() {
+        assert!(polygon_intersects_circle(&square(), pt(5.0, 5.0), 100.0));
+    }
+}
@@ -0,0 +1,256 @@
+use serde::Deserialize;
+use std::{fmt, io};
+
+use crate::game::AsteroidSize;
+
+/// Where the native game looks for its tuning, relative to the working
+/// directory; see `assets/config.toml` for the format. The web build always
+/// plays with the defaults, which match the file.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CONFIG_PATH: &str = "assets/config.toml";
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlanetConfig {
+    pub radius: f32,
+    /// How far the planet swells and shrinks either side of `radius`.
+    pub pulse: f32,
+}
+
+impl Default for PlanetConfig {
+    fn default() -> Self {
+        Self {
+            radius: 100.0,
+            pulse: 10.0,
+        }
+    }
+}
+
+/// Tuning of one size of asteroid.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AsteroidConfig {
+    /// Rough radius of the outline.
+    pub radius: f32,
+    /// Multiplier on the speed of the wave it's spawned in.
+    pub speed: f32,
+    pub score: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsteroidsConfig {
+    pub large: AsteroidConfig,
+    pub medium: AsteroidConfig,
+    pub small: AsteroidConfig,
+}
+
+impl AsteroidsConfig {
+    pub fn get(&self, size: AsteroidSize) -> &AsteroidConfig {
+        match size {
+            AsteroidSize::Large => &self.large,
+            AsteroidSize::Medium => &self.medium,
+            AsteroidSize::Small => &self.small,
+        }
+    }
+}
+
+impl Default for AsteroidsConfig {
+    fn default() -> Self {
+        Self {
+            large: AsteroidConfig {
+                radius: 40.0,
+                speed: 0.7,
+                score: 1,
+            },
+            medium: AsteroidConfig {
+                radius: 20.0,
+                speed: 1.0,
+                score: 2,
+            },
+            small: AsteroidConfig {
+                radius: 10.0,
+                speed: 1.4,
+                score: 3,
+            },
+        }
+    }
+}
+
+/// Numbers tuning how the game plays, in playfield pixels and seconds.
+/// Anything missing from a config file keeps its default.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Lives at the start of a run.
+    pub lives: u32,
+    /// Gap between the surface of the planet and the base of the ship.
+    pub ship_distance: f32,
+    /// Length of the ship from its base to its nose, where lasers are fired from.
+    pub ship_length: f32,
+    pub laser_speed: f32,
+    pub laser_length: f32,
+    /// Distance from the centre of the planet asteroids spawn at, on top of
+    /// their radius.
+    pub spawn_distance: f32,
+    /// How far either fragment of a split asteroid veers off its heading, in
+    /// degrees.
+    pub split_angle: f32,
+    pub planet: PlanetConfig,
+    pub asteroids: AsteroidsConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            lives: 5,
+            ship_distance: 30.0,
+            ship_length: 30.0,
+            laser_speed: 500.0,
+            laser_length: 30.0,
+            // just out of sight past the corners of the playfield
+            spawn_distance: 707.1,
+            split_angle: 30.0,
+            planet: PlanetConfig::default(),
+            asteroids: AsteroidsConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// The file parsed but has values the game can't be played with.
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "couldn't read config file: {}", e),
+            ConfigError::Parse(e) => write!(f, "couldn't parse config: {}", e),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+impl GameConfig {
+    pub fn from_toml(source: &str) -> Result<Self, ConfigError> {
+        let config: GameConfig = toml::from_str(source)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ConfigError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let non_negative = |name: &str, value: f32| {
+            if value.is_finite() && value >= 0.0 {
+                Ok(())
+            } else {
+                Err(ConfigError::Invalid(format!(
+                    "{} must be 0 or above, not {}",
+                    name, value
+                )))
+            }
+        };
+
+        if self.lives == 0 {
+            return Err(ConfigError::Invalid("lives must be above 0".to_string()));
+        }
+        non_negative("ship_distance", self.ship_distance)?;
+        non_negative("ship_length", self.ship_length)?;
+        if !(self.laser_speed.is_finite() && self.laser_speed > 0.0) {
+            return Err(ConfigError::Invalid(
+                "laser_speed must be above 0".to_string(),
+            ));
+        }
+        non_negative("laser_length", self.laser_length)?;
+        non_negative("spawn_distance", self.spawn_distance)?;
+        non_negative("planet.radius", self.planet.radius)?;
+        non_negative("planet.pulse", self.planet.pulse)?;
+        if !self.split_angle.is_finite() {
+            return Err(ConfigError::Invalid(
+                "split_angle must be finite".to_string(),
+            ));
+        }
+        if self.planet.pulse > self.planet.radius {
+            return Err(ConfigError::Invalid(
+                "planet.pulse can't be more than planet.radius".to_string(),
+            ));
+        }
+
+        for (name, asteroid) in [
+            ("large", &self.asteroids.large),
+            ("medium", &self.asteroids.medium),
+            ("small", &self.asteroids.small),
+        ] {
+            if !(asteroid.radius.is_finite() && asteroid.radius > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "{} asteroids must have a radius above 0",
+                    name
+                )));
+            }
+            // the wave's speed is scaled by this, so at 0 the size would sit
+            // where it spawns
+            if !(asteroid.speed.is_finite() && asteroid.speed > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "{} asteroids must have a speed above 0",
+                    name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_matches_defaults() {
+        let config = GameConfig::from_toml(include_str!("../assets/config.toml")).unwrap();
+        assert_eq!(config, GameConfig::default());
+    }
+
+    #[test]
+    fn missing_values_keep_defaults() {
+        let config = GameConfig::from_toml("lives = 3\n[planet]\nradius = 80.0").unwrap();
+        assert_eq!(config.lives, 3);
+        assert_eq!(config.planet.radius, 80.0);
+        assert_eq!(config.planet.pulse, PlanetConfig::default().pulse);
+        assert_eq!(config.laser_speed, GameConfig::default().laser_speed);
+    }
+
+    #[test]
+    fn rejects_unplayable_config() {
+        let result = GameConfig::from_toml("lives = 0");
+        assert!(matches!(result, Err(ConfigError::Invalid(_))));
+        let result =
+            GameConfig::from_toml("[asteroids.small]\nradius = -1.0\nspeed = 1.0\nscore = 1");
+        assert!(matches!(result, Err(ConfigError::Invalid(_))));
+        let result =
+            GameConfig::from_toml("[asteroids.large]\nradius = 40.0\nspeed = 0.0\nscore = 1");
+        assert!(matches!(result, Err(ConfigError::Invalid(_))));
+    }
+}
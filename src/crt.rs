@@ -0,0 +1,263 @@
+use instant::Instant;
+use wgpu::util::DeviceExt;
+
+use crate::fullscreen;
+
+/// Tunable parameters of the CRT screen effect.
+#[derive(Clone, Copy, Debug)]
+pub struct CrtSettings {
+    pub enabled: bool,
+    /// How dark the gaps between scanlines get, from 0.0 (none) to 1.0 (black).
+    pub scanline_intensity: f32,
+    /// Number of scanlines from the top to the bottom of the screen.
+    pub scanline_count: f32,
+    /// Strength of the barrel distortion, 0.0 leaves the image flat.
+    pub curvature: f32,
+    /// Fraction of the brightness kept after a sixtieth of a second, however
+    /// long frames actually take, leaving fading trails behind moving lines.
+    /// 0.0 disables persistence.
+    pub persistence: f32,
+    /// Largest fraction the brightness randomly dips by each frame.
+    pub flicker: f32,
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scanline_intensity: 0.35,
+            scanline_count: 480.0,
+            curvature: 0.06,
+            persistence: 0.8,
+            flicker: 0.04,
+        }
+    }
+}
+
+/// Vector monitor screen effect, run as its own pass after the scene (and
+/// bloom) have been rendered into `input_view`.
+pub struct Crt {
+    pub settings: CrtSettings,
+    format: wgpu::TextureFormat,
+    persist_pipeline: wgpu::RenderPipeline,
+    output_pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    frame: u32,
+    // when the last frame was rendered, None if the persisted image is stale
+    last_render: Option<Instant>,
+    // None while the surface is too large for the device's texture limits
+    targets: Option<Targets>,
+}
+
+struct Targets {
+    input_view: wgpu::TextureView,
+    // the persisted image alternates between two textures, each frame reading
+    // the one written by the frame before
+    history_views: [wgpu::TextureView; 2],
+    persist_bind_groups: [wgpu::BindGroup; 2],
+    output_bind_groups: [wgpu::BindGroup; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CrtUniform {
+    effects: [f32; 4],
+    frame: [f32; 4],
+}
+
+impl Crt {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        settings: CrtSettings,
+    ) -> Self {
+        let shader = fullscreen::shader(device, "CRT Shader", include_str!("crt.wgsl"));
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                fullscreen::texture_entry(0),
+                fullscreen::sampler_entry(1),
+                fullscreen::uniform_entry(2),
+                fullscreen::texture_entry(3),
+            ],
+            label: Some("crt_bind_group_layout"),
+        });
+
+        let pipeline = |label, entry_point| {
+            fullscreen::pipeline(device, label, &shader, &layout, entry_point, config.format)
+        };
+        let persist_pipeline = pipeline("CRT Persistence Pipeline", "fs_persist");
+        let output_pipeline = pipeline("CRT Output Pipeline", "fs_output");
+
+        let sampler = fullscreen::sampler(device, "CRT Sampler");
+
+        // filled in from the settings every frame by `render`
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("CRT Buffer"),
+            contents: bytemuck::cast_slice(&[CrtUniform {
+                effects: [0.0; 4],
+                frame: [0.0; 4],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let mut crt = Self {
+            settings,
+            format: config.format,
+            persist_pipeline,
+            output_pipeline,
+            layout,
+            sampler,
+            uniform_buffer,
+            frame: 0,
+            last_render: None,
+            targets: None,
+        };
+        crt.resize(device, config.width, config.height);
+        crt
+    }
+
+    /// Recreates the offscreen textures for a `width` x `height` surface, or
+    /// drops them if the device can't hold textures that large. This also
+    /// clears any persisted trails.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.clear_history();
+        if !fullscreen::fits(device, width, height) {
+            self.targets = None;
+            return;
+        }
+
+        let create_view =
+            |label| fullscreen::target_view(device, label, self.format, width, height);
+        let input_view = create_view("CRT Input Texture");
+        let history_views = [
+            create_view("CRT History Texture A"),
+            create_view("CRT History Texture B"),
+        ];
+
+        let bind_group = |label, source, history| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(history),
+                    },
+                ],
+                label: Some(label),
+            })
+        };
+        // writing history `i` reads the input and the other history texture,
+        // then the output pass reads back history `i` (bound twice, as the
+        // output shader doesn't use the second texture)
+        let persist_bind_groups = [
+            bind_group("crt_persist_bind_group_a", &input_view, &history_views[1]),
+            bind_group("crt_persist_bind_group_b", &input_view, &history_views[0]),
+        ];
+        let output_bind_groups = [
+            bind_group(
+                "crt_output_bind_group_a",
+                &history_views[0],
+                &history_views[0],
+            ),
+            bind_group(
+                "crt_output_bind_group_b",
+                &history_views[1],
+                &history_views[1],
+            ),
+        ];
+
+        self.targets = Some(Targets {
+            input_view,
+            history_views,
+            persist_bind_groups,
+            output_bind_groups,
+        });
+    }
+
+    /// Drops the persisted trails, so the next frame starts from a clean
+    /// screen rather than whatever was left when the effect last ran.
+    pub fn clear_history(&mut self) {
+        self.last_render = None;
+    }
+
+    /// The view the frame should be rendered into instead of the surface, if
+    /// the effect is enabled and can currently run.
+    pub fn input_view(&self) -> Option<&wgpu::TextureView> {
+        self.targets
+            .as_ref()
+            .filter(|_| self.settings.enabled)
+            .map(|targets| &targets.input_view)
+    }
+
+    /// Applies the effect to whatever was rendered into `input_view` and
+    /// writes the result to `target`.
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+    ) {
+        let targets = match &self.targets {
+            Some(targets) => targets,
+            None => return,
+        };
+
+        self.frame = self.frame.wrapping_add(1);
+        let settings = &self.settings;
+        let now = Instant::now();
+        let persistence = match self.last_render.replace(now) {
+            Some(last) => {
+                let dt = now.duration_since(last).as_secs_f32();
+                settings.persistence.powf(dt * 60.0)
+            }
+            None => 0.0,
+        };
+        let uniform = CrtUniform {
+            effects: [
+                settings.scanline_intensity,
+                settings.scanline_count,
+                settings.curvature,
+                settings.flicker,
+            ],
+            // keep the frame number small enough for the shader's noise to
+            // stay precise in f32
+            frame: [persistence, (self.frame % 1024) as f32, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+        let current = (self.frame % 2) as usize;
+        let passes = [
+            (
+                "CRT Persistence Pass",
+                &targets.history_views[current],
+                &self.persist_pipeline,
+                &targets.persist_bind_groups[current],
+            ),
+            (
+                "CRT Output Pass",
+                target,
+                &self.output_pipeline,
+                &targets.output_bind_groups[current],
+            ),
+        ];
+
+        for (label, view, pipeline, bind_group) in passes {
+            fullscreen::draw(encoder, label, view, pipeline, bind_group);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// CRT passes, run after the fullscreen vertex shader in fullscreen.wgsl
+
+struct CrtUniform {
+    // x: scanline intensity, y: scanline count, z: curvature, w: flicker
+    effects: vec4<f32>,
+    // x: persistence, y: frame number, zw: unused padding
+    frame: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var linear_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> crt: CrtUniform;
+@group(0) @binding(3)
+var history: texture_2d<f32>;
+
+// Persistence pass: keeps a decaying copy of earlier frames, like phosphor
+// that hasn't finished fading
+
+@fragment
+fn fs_persist(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let current = textureSample(source, linear_sampler, in.uv).rgb;
+    let previous = textureSample(history, linear_sampler, in.uv).rgb;
+    return vec4<f32>(max(current, previous * crt.frame.x), 1.0);
+}
+
+// Output pass: bends, scans and flickers the persisted image onto the screen
+
+@fragment
+fn fs_output(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    // barrel distortion, pushing the image out towards the corners
+    let centered = in.uv * 2.0 - 1.0;
+    let bent = centered * (1.0 + crt.effects.z * centered.yx * centered.yx);
+    let uv = bent * 0.5 + 0.5;
+
+    var color = textureSample(source, linear_sampler, uv).rgb;
+
+    let scanline = 0.5 + 0.5 * cos(uv.y * crt.effects.y * 6.2831853);
+    color = color * mix(1.0, scanline, crt.effects.x);
+
+    let noise = fract(sin(crt.frame.y * 12.9898) * 43758.5453);
+    color = color * (1.0 - crt.effects.w * noise);
+
+    // black outside the bent screen
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return vec4<f32>(select(vec3<f32>(0.0), color, inside), 1.0);
+}
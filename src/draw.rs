@@ -1,17 +1,18 @@
-use crate::{HEIGHT, WIDTH, Vertex};
+use crate::{font, HEIGHT, WIDTH, QuadVertex, Vertex};
 
-/// screen space representation, coordinates range from 0 .. screen size
-# [derive(Clone, Copy, Debug)]
+/// playfield space representation, coordinates range from 0 .. `WIDTH`/`HEIGHT`
+/// whatever the size of the window, see [`Viewport`]
+# [derive(Clone, Copy, Debug, PartialEq)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
 }
 
 impl Point {
-    pub fn to_draw_space(&self) -> DrawSpacePoint {
+    pub fn to_draw_space(self) -> DrawSpacePoint {
         DrawSpacePoint {
-            x: (self.x as f32 - (WIDTH as f32 / 2.0)) / (WIDTH as f32 / 2.0),
-            y: -1.0 * ((self.y as f32 - (HEIGHT as f32 / 2.0)) / (HEIGHT as f32 / 2.0)),
+            x: (self.x - (WIDTH as f32 / 2.0)) / (WIDTH as f32 / 2.0),
+            y: -((self.y - (HEIGHT as f32 / 2.0)) / (HEIGHT as f32 / 2.0)),
         }
     }
 }
@@ -24,123 +25,368 @@ pub struct DrawSpacePoint {
 }
 
 impl DrawSpacePoint {
-    pub fn _to_screen_space(&self) -> Point {
+    pub fn _to_screen_space(self) -> Point {
         Point {
             x: ((self.x * WIDTH as f32 / 2.0) + WIDTH as f32 / 2.0),
-            y: ((-1.0 * self.y * HEIGHT as f32 / 2.0) + HEIGHT as f32 / 2.0),
+            y: ((-self.y * HEIGHT as f32 / 2.0) + HEIGHT as f32 / 2.0),
         }
     }
 }
 
+/// linear rgb, each channel ranges from 0.0 .. 1.0
+pub type Color = [f32; 3];
+
+pub const WHITE: Color = [1.0, 1.0, 1.0];
+pub const RED: Color = [1.0, 0.2, 0.2];
+pub const ORANGE: Color = [1.0, 0.6, 0.2];
+pub const YELLOW: Color = [1.0, 0.9, 0.3];
+pub const GREEN: Color = [0.3, 1.0, 0.4];
+pub const CYAN: Color = [0.3, 0.9, 1.0];
+pub const GREY: Color = [0.7, 0.7, 0.7];
+
 pub struct LineHandler {
-    pub vertices: Vec<Vertex>
+    pub vertices: Vec<Vertex>,
+    // lines added with `add_line` use the colour on top of the stack
+    color_stack: Vec<Color>,
 }
 
 impl LineHandler {
     pub fn new() -> Self {
         LineHandler {
             vertices: Vec::new(),
+            color_stack: Vec::new(),
         }
     }
 
+    /// Adds a line in the current colour, see [`LineHandler::push_color`].
     pub fn add_line(&mut self, p1: Point, p2: Point) {
+        self.add_line_colored(p1, p2, self.color());
+    }
+
+    pub fn add_line_colored(&mut self, p1: Point, p2: Point, color: Color) {
         let p1_ds = p1.to_draw_space();
         let p2_ds = p2.to_draw_space();
         
         self.vertices.push(Vertex {
             position: [p1_ds.x, p1_ds.y, 0.0],
-            color: [1.0, 1.0, 1.0]
+            color,
         });
         self.vertices.push(Vertex {
             position: [p2_ds.x, p2_ds.y, 0.0],
-            color: [1.0, 1.0, 1.0]
+            color,
         });
     }
 
+    /// The colour `add_line` currently draws with, white if none was pushed.
+    pub fn color(&self) -> Color {
+        self.color_stack.last().copied().unwrap_or(WHITE)
+    }
+
+    /// Makes `add_line` draw in `color` until the matching `pop_color`.
+    pub fn push_color(&mut self, color: Color) {
+        self.color_stack.push(color);
+    }
+
+    pub fn pop_color(&mut self) {
+        self.color_stack.pop();
+    }
+
     pub fn clear_lines(&mut self) {
         self.vertices.clear();
+        self.color_stack.clear();
+    }
+}
+
+impl Default for LineHandler {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-pub fn draw_text(lh: &mut LineHandler, text: &str, mut x: f32, y: f32) {
-    for char in text.chars() {
-        match char {
-            '0' => {
-                lh.add_line(Point {x, y}, Point { x, y: y + 40.0 });
-                lh.add_line(Point {x, y: y + 40.0 }, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 40.0}, Point {x: x + 20.0, y});
-                lh.add_line(Point {x: x + 20.0, y}, Point {x, y});
-                // lh.add_line(Point {x, y: y + 40.0}, Point {x: x + 20.0, y});
-            },
-            '1' => {
-                lh.add_line(Point {x, y: y + 40.0}, Point { x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 10.0, y: y + 40.0}, Point { x: x + 10.0, y});
-                lh.add_line(Point {x: x + 10.0, y}, Point { x, y: y + 10.0});
-            },
-            '2' => {
-                lh.add_line(Point {x, y}, Point { x: x + 20.0, y });
-                lh.add_line(Point {x: x + 20.0, y }, Point { x: x + 20.0, y: y + 20.0 });
-                lh.add_line(Point {x: x + 20.0, y: y + 20.0 }, Point { x, y: y + 20.0 });
-                lh.add_line(Point {x, y: y + 20.0 }, Point { x, y: y + 40.0 });
-                lh.add_line(Point {x, y: y + 40.0 }, Point { x: x + 20.0, y: y + 40.0 });
-            },
-            '3' => {
-                lh.add_line(Point {x, y}, Point { x: x + 20.0, y });
-                lh.add_line(Point {x: x + 20.0, y}, Point { x: x + 20.0, y: y + 40.0 });
-                lh.add_line(Point {x: x + 20.0, y: y + 40.0}, Point { x, y: y + 40.0 });
-                lh.add_line(Point {x: x + 20.0, y: y + 20.0}, Point { x, y: y + 20.0 });
-            },
-            '4' => {
-                lh.add_line(Point {x, y}, Point {x, y: y + 20.0});
-                lh.add_line(Point {x, y: y + 20.0}, Point {x: x + 20.0, y: y + 20.0});
-                lh.add_line(Point {x: x + 20.0, y}, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 20.0, y}, Point {x: x + 20.0, y: y + 40.0});
-            },
-            '5' => {
-                lh.add_line(Point {x: x + 20.0, y}, Point {x, y});
-                lh.add_line(Point {x, y}, Point {x, y: y + 20.0});
-                lh.add_line(Point {x, y: y + 20.0}, Point {x: x + 20.0, y: y + 20.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 20.0}, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 40.0}, Point {x, y: y + 40.0});
-                
-            },
-            '6' => {
-                lh.add_line(Point {x: x + 20.0, y}, Point {x, y});
-                lh.add_line(Point {x, y}, Point {x, y: y + 40.0});
-                lh.add_line(Point {x, y: y + 40.0}, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 40.0}, Point {x: x + 20.0, y: y + 20.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 20.0}, Point {x, y: y + 20.0});
-            },
-            '7' => {
-                lh.add_line(Point {x, y}, Point {x: x + 20.0, y});
-                lh.add_line(Point {x: x + 20.0, y}, Point {x, y: y + 40.0});
-            },
-            '8' => {
-                lh.add_line(Point {x, y}, Point { x, y: y + 40.0 });
-                lh.add_line(Point {x, y: y + 40.0 }, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point {x: x + 20.0, y: y + 40.0}, Point {x: x + 20.0, y});
-                lh.add_line(Point {x: x + 20.0, y}, Point {x, y});
-                lh.add_line(Point {x, y: y + 20.0}, Point {x: x + 20.0, y: y + 20.0});
-            },
-            '9' => {
-                lh.add_line(Point{x,y}, Point {x: x + 20.0, y});
-                lh.add_line(Point{x: x + 20.0,y}, Point {x: x + 20.0, y: y + 40.0});
-                lh.add_line(Point{x: x + 20.0, y: y + 40.0}, Point {x, y: y + 40.0});
-                lh.add_line(Point{x,y}, Point {x, y: y + 20.0});
-                lh.add_line(Point{x, y: y + 20.0}, Point {x: x + 20.0, y: y + 20.0});
-            },
-            '.' => {
-                lh.add_line(Point {x, y: y + 40.0}, Point { x: x + 2.0, y: y + 40.0 });
-                lh.add_line(Point {x: x + 2.0, y: y + 40.0}, Point { x: x + 2.0, y: y + 38.0 });
-                lh.add_line(Point {x: x + 2.0, y: y + 38.0}, Point { x, y: y + 38.0 });
-                lh.add_line(Point {x, y: y + 38.0}, Point { x, y: y + 40.0 });
-                x -= 18.0;
-            }
-            _ => {
-                x -= 30.0;
-            },
+/// Maps the logical playfield onto a window of any size: the playfield is
+/// scaled uniformly to fit and centred, leaving black bars along the sides
+/// that don't match its aspect ratio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Size of the surface in physical pixels.
+    pub surface_width: f32,
+    pub surface_height: f32,
+    /// Physical pixels per playfield unit.
+    pub scale: f32,
+    /// Top left corner of the playfield on the surface, in physical pixels.
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Viewport {
+    pub fn new(surface_width: f32, surface_height: f32) -> Self {
+        let scale = (surface_width / WIDTH as f32).min(surface_height / HEIGHT as f32);
+        Self {
+            surface_width,
+            surface_height,
+            scale,
+            x: (surface_width - WIDTH as f32 * scale) / 2.0,
+            y: (surface_height - HEIGHT as f32 * scale) / 2.0,
+        }
+    }
+
+    /// Size of the playfield on the surface, in physical pixels.
+    pub fn width(&self) -> f32 {
+        WIDTH as f32 * self.scale
+    }
+
+    pub fn height(&self) -> f32 {
+        HEIGHT as f32 * self.scale
+    }
+
+    /// Converts a position on the surface in physical pixels to the
+    /// playfield. Positions in the bars land outside `0 .. WIDTH`/`HEIGHT`.
+    pub fn to_playfield(&self, x: f32, y: f32) -> Point {
+        Point {
+            x: (x - self.x) / self.scale,
+            y: (y - self.y) / self.scale,
+        }
+    }
+}
+
+/// Expands each pair of line vertices into a quad of two triangles `width`
+/// physical pixels wide and appends them to `out`, in physical pixels from the
+/// centre of the playfield as laid out by `viewport`. Quads reach one pixel
+/// past the line on every side, which the shader fades out to anti-alias the
+/// edges. `zoom` is how much the camera magnifies the quads afterwards, which
+/// they're laid out thinner or thicker to make up for.
+pub fn expand_lines(
+    lines: &[Vertex],
+    width: f32,
+    zoom: f32,
+    viewport: &Viewport,
+    out: &mut Vec<QuadVertex>,
+) {
+    let half_width = width / 2.0;
+    let reach = half_width + 1.0;
+    let (sx, sy) = (viewport.width() / 2.0, viewport.height() / 2.0);
+
+    for line in lines.chunks_exact(2) {
+        let (a, b) = (line[0], line[1]);
+
+        // work in pixels so the line is equally wide in every direction
+        let (ax, ay) = (a.position[0] * sx, a.position[1] * sy);
+        let (bx, by) = (b.position[0] * sx, b.position[1] * sy);
+        let len = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+        if len == 0.0 {
+            continue;
+        }
+
+        // along and across the line, extending past both ends as square caps,
+        // while `edge` stays in pixels as they'll be seen
+        let across = reach / zoom;
+        let (ux, uy) = ((bx - ax) / len * across, (by - ay) / len * across);
+        let (nx, ny) = (-uy, ux);
+
+        let corner = |x: f32, y: f32, color, edge: f32| QuadVertex {
+            position: [x, y, 0.0],
+            color,
+            edge: [edge, half_width],
         };
+        let s1 = corner(ax - ux + nx, ay - uy + ny, a.color, reach);
+        let s2 = corner(ax - ux - nx, ay - uy - ny, a.color, -reach);
+        let e1 = corner(bx + ux + nx, by + uy + ny, b.color, reach);
+        let e2 = corner(bx + ux - nx, by + uy - ny, b.color, -reach);
+
+        out.extend_from_slice(&[s1, s2, e1, e1, s2, e2]);
+    }
+}
+
+/// Horizontal alignment of text relative to the `x` it's drawn at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    /// Height of a glyph in pixels, glyphs are half as wide.
+    pub size: f32,
+    pub align: Align,
+    /// Extra space between glyphs in pixels, on top of the font's own spacing.
+    pub letter_spacing: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 40.0,
+            align: Align::Left,
+            letter_spacing: 0.0,
+        }
+    }
+}
+
+/// Axis-aligned box in screen space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Returns the box `text` covers when drawn at `x, y` in `style`.
+pub fn measure_text(text: &str, x: f32, y: f32, style: &TextStyle) -> Rect {
+    let unit = style.size / font::GLYPH_HEIGHT;
+
+    let mut advance = 0.0;
+    let mut count = 0;
+    for c in text.chars() {
+        advance += font::glyph_for(c).advance;
+        count += 1;
+    }
+    // the last glyph's trailing spacing isn't part of the text
+    let width = if count == 0 {
+        0.0
+    } else {
+        (advance - font::SPACING) * unit + style.letter_spacing * (count - 1) as f32
+    };
+
+    let x = match style.align {
+        Align::Left => x,
+        Align::Center => x - width / 2.0,
+        Align::Right => x - width,
+    };
+
+    Rect {
+        x,
+        y,
+        width,
+        height: style.size,
+    }
+}
+
+/// Draws `text` with its top-left corner at `x, y`, each glyph 20px wide and
+/// 40px tall.
+pub fn draw_text(lh: &mut LineHandler, text: &str, x: f32, y: f32) {
+    draw_text_styled(lh, text, x, y, &TextStyle::default());
+}
+
+/// Draws `text` at `x, y` in `style`, where `y` is the top of the text and
+/// `x` is its left edge, centre or right edge depending on the alignment.
+pub fn draw_text_styled(lh: &mut LineHandler, text: &str, x: f32, y: f32, style: &TextStyle) {
+    let unit = style.size / font::GLYPH_HEIGHT;
+    let mut x = measure_text(text, x, y, style).x;
+
+    for c in text.chars() {
+        let glyph = font::glyph_for(c);
+        for [x1, y1, x2, y2] in glyph.strokes {
+            lh.add_line(
+                Point { x: x + x1 * unit, y: y + y1 * unit },
+                Point { x: x + x2 * unit, y: y + y2 * unit },
+            );
+        }
+        x += glyph.advance * unit + style.letter_spacing;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: f32 = WIDTH as f32;
+    const H: f32 = HEIGHT as f32;
 
-        x += 30.0;
+    fn assert_corners_map_to_the_playfield(vp: &Viewport) {
+        assert_eq!(vp.to_playfield(vp.x, vp.y), Point { x: 0.0, y: 0.0 });
+        assert_eq!(
+            vp.to_playfield(vp.x + vp.width(), vp.y + vp.height()),
+            Point { x: W, y: H }
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn wide_windows_get_bars_on_the_sides() {
+        let vp = Viewport::new(2.0 * W, H);
+        assert_eq!(vp.scale, 1.0);
+        assert_eq!((vp.x, vp.y), (W / 2.0, 0.0));
+        assert_eq!((vp.width(), vp.height()), (W, H));
+        assert_corners_map_to_the_playfield(&vp);
+
+        let left_bar = vp.to_playfield(W / 4.0, H / 2.0);
+        let right_bar = vp.to_playfield(2.0 * W - W / 4.0, H / 2.0);
+        assert!(left_bar.x < 0.0);
+        assert!(right_bar.x > W);
+        assert!((0.0..=H).contains(&left_bar.y));
+    }
+
+    #[test]
+    fn tall_windows_get_bars_on_top_and_bottom() {
+        let vp = Viewport::new(W, 2.0 * H);
+        assert_eq!(vp.scale, 1.0);
+        assert_eq!((vp.x, vp.y), (0.0, H / 2.0));
+        assert_eq!((vp.width(), vp.height()), (W, H));
+        assert_corners_map_to_the_playfield(&vp);
+
+        let top_bar = vp.to_playfield(W / 2.0, H / 4.0);
+        let bottom_bar = vp.to_playfield(W / 2.0, 2.0 * H - H / 4.0);
+        assert!(top_bar.y < 0.0);
+        assert!(bottom_bar.y > H);
+        assert!((0.0..=W).contains(&top_bar.x));
+    }
+
+    #[test]
+    fn matching_windows_have_no_bars() {
+        let vp = Viewport::new(2.0 * W, 2.0 * H);
+        assert_eq!(vp.scale, 2.0);
+        assert_eq!((vp.x, vp.y), (0.0, 0.0));
+        assert_corners_map_to_the_playfield(&vp);
+    }
+
+    fn style(align: Align, letter_spacing: f32) -> TextStyle {
+        TextStyle {
+            size: 40.0,
+            align,
+            letter_spacing,
+        }
+    }
+
+    #[test]
+    fn alignment_moves_the_text_left_of_x() {
+        let left = measure_text("SCORE", 100.0, 50.0, &style(Align::Left, 0.0));
+        let center = measure_text("SCORE", 100.0, 50.0, &style(Align::Center, 0.0));
+        let right = measure_text("SCORE", 100.0, 50.0, &style(Align::Right, 0.0));
+        assert_eq!(left.x, 100.0);
+        assert_eq!(center.x, 100.0 - left.width / 2.0);
+        assert_eq!(right.x, 100.0 - left.width);
+        assert_eq!(center.width, left.width);
+        assert_eq!(right.width, left.width);
+        assert_eq!((left.y, center.y, right.y), (50.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn letter_spacing_only_goes_between_glyphs() {
+        let width =
+            |text, spacing| measure_text(text, 0.0, 0.0, &style(Align::Left, spacing)).width;
+        assert_eq!(width("0", 10.0), width("0", 0.0));
+        assert_eq!(width("00", 10.0), width("00", 0.0) + 10.0);
+        assert_eq!(width("0000", 10.0), width("0000", 0.0) + 30.0);
+    }
+
+    #[test]
+    fn empty_text_has_no_width() {
+        for align in [Align::Left, Align::Center, Align::Right] {
+            let rect = measure_text("", 100.0, 50.0, &style(align, 10.0));
+            assert_eq!(rect.width, 0.0);
+            assert_eq!(rect.x, 100.0);
+        }
+    }
+
+    #[test]
+    fn trailing_spacing_is_left_out() {
+        let unit = 40.0 / font::GLYPH_HEIGHT;
+        let advance = font::glyph_for('0').advance;
+        let one = measure_text("0", 0.0, 0.0, &style(Align::Left, 0.0));
+        assert_eq!(one.width, (advance - font::SPACING) * unit);
+        let two = measure_text("00", 0.0, 0.0, &style(Align::Left, 0.0));
+        assert_eq!(two.width, (2.0 * advance - font::SPACING) * unit);
+    }
+}
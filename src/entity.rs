@@ -0,0 +1,252 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// Handle to a `T` in a [`Store`]. It stays valid for as long as that entity
+/// is alive, however many others are added or removed, and never refers to a
+/// different entity that later reuses the same slot.
+pub struct Id<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// implemented by hand, deriving would require `T` to implement them too
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    // bumped every time the slot is freed, invalidating old ids
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Slot map of entities of one kind, addressed by generational [`Id`]s.
+/// Freed slots are reused, most recently freed first, and iteration is in
+/// slot order, so a store filled and emptied the same way always ends up
+/// the same, which keeps the simulation deterministic.
+pub struct Store<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Store<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Id<T> {
+        self.len += 1;
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        Id {
+            index,
+            generation: self.slots[index as usize].generation,
+            _marker: PhantomData,
+        }
+    }
+
+    fn slot(&self, id: Id<T>) -> Option<&Slot<T>> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+    }
+
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        self.slot(id)?.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)?
+            .value
+            .as_mut()
+    }
+
+    pub fn contains(&self, id: Id<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Takes the entity out of the store, or returns `None` if it was already
+    /// removed.
+    pub fn remove(&mut self, id: Id<T>) -> Option<T> {
+        let slot = self
+            .slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Removes every entity, invalidating all their ids.
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Removes every entity `keep` returns false for.
+    pub fn retain(&mut self, mut keep: impl FnMut(Id<T>, &mut T) -> bool) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            let id = Id {
+                index: index as u32,
+                generation: slot.generation,
+                _marker: PhantomData,
+            };
+            if let Some(value) = &mut slot.value {
+                if !keep(id, value) {
+                    slot.value = None;
+                    slot.generation = slot.generation.wrapping_add(1);
+                    self.free.push(index as u32);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let id = Id {
+                index: index as u32,
+                generation: slot.generation,
+                _marker: PhantomData,
+            };
+            slot.value.as_ref().map(|value| (id, value))
+        })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+}
+
+impl<T> Default for Store<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Store<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_survive_other_removals() {
+        let mut store = Store::new();
+        let a = store.insert('a');
+        let b = store.insert('b');
+        let c = store.insert('c');
+        assert_eq!(store.remove(b), Some('b'));
+        assert_eq!(store.get(a), Some(&'a'));
+        assert_eq!(store.get(c), Some(&'c'));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn stale_ids_miss_reused_slots() {
+        let mut store = Store::new();
+        let a = store.insert('a');
+        store.remove(a);
+        let b = store.insert('b');
+        assert_eq!(store.get(a), None);
+        assert_eq!(store.remove(a), None);
+        assert_eq!(store.get(b), Some(&'b'));
+    }
+
+    #[test]
+    fn retain_and_clear_invalidate_ids() {
+        let mut store = Store::new();
+        let ids: Vec<_> = (0..4).map(|i| store.insert(i)).collect();
+        store.retain(|_, value| *value % 2 == 0);
+        assert_eq!(store.values().copied().collect::<Vec<_>>(), [0, 2]);
+        assert!(!store.contains(ids[1]));
+
+        store.clear();
+        assert!(store.is_empty());
+        assert!(ids.iter().all(|&id| !store.contains(id)));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{draw::Point, game::GameEvent};
+
+/// Shake distance a planet hit starts at, in playfield units.
+const SHAKE_DISTANCE: f32 = 10.0;
+
+/// Fraction of the shake left after a second.
+const SHAKE_DECAY: f32 = 0.002;
+
+/// How long the simulation freezes when the planet is hit, in milliseconds.
+const HIT_STOP_MS: u128 = 80;
+
+/// How long the planet outline flashes after a hit, in milliseconds.
+const FLASH_MS: u128 = 150;
+
+/// Which kinds of impact feedback are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedbackSettings {
+    pub shake: bool,
+    pub hit_stop: bool,
+    pub flash: bool,
+}
+
+impl FeedbackSettings {
+    /// Everything that moves, stalls or flashes the screen is left out.
+    pub fn reduced_motion() -> Self {
+        Self {
+            shake: false,
+            hit_stop: false,
+            flash: false,
+        }
+    }
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self {
+            shake: true,
+            hit_stop: true,
+            flash: true,
+        }
+    }
+}
+
+/// Presentation effects played in response to the events a
+/// [`Game`](crate::game::Game) emits. They run on wall time and never feed
+/// back into the simulation, so replays play out the same with any settings.
+#[derive(Debug)]
+pub struct Feedback {
+    pub settings: FeedbackSettings,
+    // milliseconds of wall time, only used to animate the shake
+    time: u128,
+    shake: f32,
+    hit_stop: u128,
+    flash: u128,
+}
+
+impl Feedback {
+    pub fn new(settings: FeedbackSettings) -> Self {
+        Self {
+            settings,
+            time: 0,
+            shake: 0.0,
+            hit_stop: 0,
+            flash: 0,
+        }
+    }
+
+    /// Starts whatever effects `event` calls for.
+    pub fn trigger(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::PlanetHit => {
+                if self.settings.shake {
+                    self.shake = self.shake.max(SHAKE_DISTANCE);
+                }
+                if self.settings.hit_stop {
+                    self.hit_stop = HIT_STOP_MS;
+                }
+                if self.settings.flash {
+                    self.flash = FLASH_MS;
+                }
+            }
+            GameEvent::AsteroidDestroyed | GameEvent::GameOver => {}
+        }
+    }
+
+    /// Plays the effects forward by `dt` milliseconds of wall time and returns
+    /// how much of it the simulation should advance by, which is less while a
+    /// hit-stop is holding it.
+    pub fn advance(&mut self, dt: u128) -> u128 {
+        self.time += dt;
+        self.shake *= SHAKE_DECAY.powf(dt as f32 / 1000.0);
+        if self.shake < 0.1 || !self.settings.shake {
+            self.shake = 0.0;
+        }
+        self.flash = self.flash.saturating_sub(dt);
+        if !self.settings.flash {
+            self.flash = 0;
+        }
+
+        if !self.settings.hit_stop {
+            self.hit_stop = 0;
+        }
+        let held = dt.min(self.hit_stop);
+        self.hit_stop -= held;
+        dt - held
+    }
+
+    /// How far the camera is currently knocked off centre, in playfield units.
+    pub fn shake_offset(&self) -> Point {
+        // wobbles along two unrelated frequencies so the shake doesn't trace a
+        // visible pattern
+        let t = self.time as f32;
+        Point {
+            x: self.shake * (t * 0.11).sin(),
+            y: self.shake * (t * 0.07 + 1.0).cos(),
+        }
+    }
+
+    /// Whether the planet outline should currently be drawn in its hit colour.
+    pub fn planet_flash(&self) -> bool {
+        self.flash > 0
+    }
+}
+
+impl Default for Feedback {
+    fn default() -> Self {
+        Self::new(FeedbackSettings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(settings: FeedbackSettings) -> Feedback {
+        let mut feedback = Feedback::new(settings);
+        feedback.trigger(GameEvent::PlanetHit);
+        feedback
+    }
+
+    #[test]
+    fn hit_stop_holds_the_simulation() {
+        let mut feedback = hit(FeedbackSettings::default());
+        assert_eq!(feedback.advance(50), 0);
+        assert_eq!(feedback.advance(50), 100 - HIT_STOP_MS);
+        assert_eq!(feedback.advance(16), 16);
+    }
+
+    #[test]
+    fn shake_decays_to_nothing() {
+        let mut feedback = hit(FeedbackSettings::default());
+        assert_eq!(feedback.shake, SHAKE_DISTANCE);
+
+        let mut last = feedback.shake;
+        for _ in 0..10 {
+            feedback.advance(50);
+            assert!(feedback.shake < last);
+            let offset = feedback.shake_offset();
+            assert!(offset.x.hypot(offset.y) <= feedback.shake * 2.0f32.sqrt());
+            last = feedback.shake;
+        }
+        // half a second in, the decay is independent of the frame length
+        let expected = SHAKE_DISTANCE * SHAKE_DECAY.sqrt();
+        assert!(
+            (feedback.shake - expected).abs() < 1e-3,
+            "{}",
+            feedback.shake
+        );
+
+        feedback.advance(1000);
+        assert_eq!(feedback.shake, 0.0);
+        assert_eq!(feedback.shake_offset(), Point { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn flash_lasts_its_time() {
+        let mut feedback = hit(FeedbackSettings::default());
+        assert!(feedback.planet_flash());
+        feedback.advance(FLASH_MS - 1);
+        assert!(feedback.planet_flash());
+        feedback.advance(1);
+        assert!(!feedback.planet_flash());
+    }
+
+    #[test]
+    fn reduced_motion_leaves_everything_out() {
+        let mut feedback = hit(FeedbackSettings::reduced_motion());
+        assert!(!feedback.planet_flash());
+        assert_eq!(feedback.advance(16), 16);
+        assert_eq!(feedback.shake_offset(), Point { x: 0.0, y: 0.0 });
+
+        // switching to it midway through a hit cuts the effects short
+        let mut feedback = hit(FeedbackSettings::default());
+        feedback.settings = FeedbackSettings::reduced_motion();
+        assert_eq!(feedback.advance(16), 16);
+        assert!(!feedback.planet_flash());
+        assert_eq!(feedback.shake_offset(), Point { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn only_planet_hits_have_feedback() {
+        let mut feedback = Feedback::default();
+        feedback.trigger(GameEvent::AsteroidDestroyed);
+        feedback.trigger(GameEvent::GameOver);
+        assert!(!feedback.planet_flash());
+        assert_eq!(feedback.advance(16), 16);
+        assert_eq!(feedback.shake_offset(), Point { x: 0.0, y: 0.0 });
+    }
+}
@@ -0,0 +1,110 @@
+/// Stroke font used by `draw::draw_text`.
+///
+/// Glyphs are drawn on a grid 2 units wide and 4 units tall with y pointing
+/// down, where one unit is a quarter of the text size. Each stroke is a line
+/// segment `[x1, y1, x2, y2]` on that grid.
+pub struct Glyph {
+    pub strokes: &'static [[f32; 4]],
+    /// How far the pen moves right after drawing the glyph, in grid units.
+    pub advance: f32,
+}
+
+/// Height of the glyph grid in units.
+pub const GLYPH_HEIGHT: f32 = 4.0;
+
+/// Space every glyph's advance leaves after it, in grid units.
+pub const SPACING: f32 = 1.0;
+
+/// Advance of most glyphs: the glyph itself plus the spacing.
+const ADVANCE: f32 = 2.0 + SPACING;
+
+const fn glyph(strokes: &'static [[f32; 4]]) -> Glyph {
+    Glyph {
+        strokes,
+        advance: ADVANCE,
+    }
+}
+
+#[rustfmt::skip]
+static GLYPHS: &[(char, Glyph)] = &[
+    (' ', glyph(&[])),
+
+    ('0', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0], [2.0, 0.0, 0.0, 0.0]])),
+    ('1', glyph(&[[0.0, 4.0, 2.0, 4.0], [1.0, 4.0, 1.0, 0.0], [1.0, 0.0, 0.0, 1.0]])),
+    ('2', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 2.0], [2.0, 2.0, 0.0, 2.0], [0.0, 2.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0]])),
+    ('3', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 4.0], [2.0, 4.0, 0.0, 4.0], [2.0, 2.0, 0.0, 2.0]])),
+    ('4', glyph(&[[0.0, 0.0, 0.0, 2.0], [0.0, 2.0, 2.0, 2.0], [2.0, 0.0, 2.0, 4.0]])),
+    ('5', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 2.0], [0.0, 2.0, 2.0, 2.0], [2.0, 2.0, 2.0, 4.0], [2.0, 4.0, 0.0, 4.0]])),
+    ('6', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 2.0], [2.0, 2.0, 0.0, 2.0]])),
+    ('7', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 0.0, 4.0]])),
+    ('8', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0], [2.0, 0.0, 0.0, 0.0], [0.0, 2.0, 2.0, 2.0]])),
+    ('9', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 4.0], [2.0, 4.0, 0.0, 4.0], [0.0, 0.0, 0.0, 2.0], [0.0, 2.0, 2.0, 2.0]])),
+
+    ('A', glyph(&[[0.0, 4.0, 0.0, 1.0], [0.0, 1.0, 1.0, 0.0], [1.0, 0.0, 2.0, 1.0], [2.0, 1.0, 2.0, 4.0], [0.0, 2.0, 2.0, 2.0]])),
+    ('B', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 0.0, 1.5, 0.0], [1.5, 0.0, 2.0, 0.5], [2.0, 0.5, 2.0, 1.5], [2.0, 1.5, 1.5, 2.0],
+                  [0.0, 2.0, 1.5, 2.0], [1.5, 2.0, 2.0, 2.5], [2.0, 2.5, 2.0, 3.5], [2.0, 3.5, 1.5, 4.0], [1.5, 4.0, 0.0, 4.0]])),
+    ('C', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0]])),
+    ('D', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 0.0, 1.0, 0.0], [1.0, 0.0, 2.0, 1.0], [2.0, 1.0, 2.0, 3.0], [2.0, 3.0, 1.0, 4.0], [1.0, 4.0, 0.0, 4.0]])),
+    ('E', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [0.0, 2.0, 1.5, 2.0]])),
+    ('F', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 4.0], [0.0, 2.0, 1.5, 2.0]])),
+    ('G', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 2.0], [2.0, 2.0, 1.0, 2.0]])),
+    ('H', glyph(&[[0.0, 0.0, 0.0, 4.0], [2.0, 0.0, 2.0, 4.0], [0.0, 2.0, 2.0, 2.0]])),
+    ('I', glyph(&[[0.0, 0.0, 2.0, 0.0], [1.0, 0.0, 1.0, 4.0], [0.0, 4.0, 2.0, 4.0]])),
+    ('J', glyph(&[[2.0, 0.0, 2.0, 4.0], [2.0, 4.0, 0.0, 4.0], [0.0, 4.0, 0.0, 3.0]])),
+    ('K', glyph(&[[0.0, 0.0, 0.0, 4.0], [2.0, 0.0, 0.0, 2.0], [0.0, 2.0, 2.0, 4.0]])),
+    ('L', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0]])),
+    ('M', glyph(&[[0.0, 4.0, 0.0, 0.0], [0.0, 0.0, 1.0, 2.0], [1.0, 2.0, 2.0, 0.0], [2.0, 0.0, 2.0, 4.0]])),
+    ('N', glyph(&[[0.0, 4.0, 0.0, 0.0], [0.0, 0.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0]])),
+    ('O', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0], [2.0, 0.0, 0.0, 0.0]])),
+    ('P', glyph(&[[0.0, 4.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 2.0], [2.0, 2.0, 0.0, 2.0]])),
+    ('Q', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0], [2.0, 0.0, 0.0, 0.0], [1.0, 3.0, 2.0, 4.0]])),
+    ('R', glyph(&[[0.0, 4.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 2.0], [2.0, 2.0, 0.0, 2.0], [0.0, 2.0, 2.0, 4.0]])),
+    ('S', glyph(&[[2.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 2.0], [0.0, 2.0, 2.0, 2.0], [2.0, 2.0, 2.0, 4.0], [2.0, 4.0, 0.0, 4.0]])),
+    ('T', glyph(&[[0.0, 0.0, 2.0, 0.0], [1.0, 0.0, 1.0, 4.0]])),
+    ('U', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0]])),
+    ('V', glyph(&[[0.0, 0.0, 1.0, 4.0], [1.0, 4.0, 2.0, 0.0]])),
+    ('W', glyph(&[[0.0, 0.0, 0.0, 4.0], [0.0, 4.0, 1.0, 2.0], [1.0, 2.0, 2.0, 4.0], [2.0, 4.0, 2.0, 0.0]])),
+    ('X', glyph(&[[0.0, 0.0, 2.0, 4.0], [2.0, 0.0, 0.0, 4.0]])),
+    ('Y', glyph(&[[0.0, 0.0, 1.0, 2.0], [2.0, 0.0, 1.0, 2.0], [1.0, 2.0, 1.0, 4.0]])),
+    ('Z', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 0.0, 4.0], [0.0, 4.0, 2.0, 4.0]])),
+
+    ('.', Glyph {
+        strokes: &[[0.0, 4.0, 0.2, 4.0], [0.2, 4.0, 0.2, 3.8], [0.2, 3.8, 0.0, 3.8], [0.0, 3.8, 0.0, 4.0]],
+        advance: 1.2,
+    }),
+    (',', Glyph {
+        strokes: &[[0.2, 3.6, 0.2, 4.0], [0.2, 4.0, 0.0, 4.4]],
+        advance: 1.2,
+    }),
+    (':', Glyph {
+        strokes: &[[0.0, 1.0, 0.2, 1.0], [0.2, 1.0, 0.2, 1.2], [0.2, 1.2, 0.0, 1.2], [0.0, 1.2, 0.0, 1.0],
+                   [0.0, 4.0, 0.2, 4.0], [0.2, 4.0, 0.2, 3.8], [0.2, 3.8, 0.0, 3.8], [0.0, 3.8, 0.0, 4.0]],
+        advance: 1.2,
+    }),
+    ('!', glyph(&[[1.0, 0.0, 1.0, 2.8], [1.0, 3.8, 1.0, 4.0]])),
+    ('?', glyph(&[[0.0, 0.0, 2.0, 0.0], [2.0, 0.0, 2.0, 2.0], [2.0, 2.0, 1.0, 2.0], [1.0, 2.0, 1.0, 2.8], [1.0, 3.8, 1.0, 4.0]])),
+    ('-', glyph(&[[0.0, 2.0, 2.0, 2.0]])),
+    ('+', glyph(&[[0.0, 2.0, 2.0, 2.0], [1.0, 1.0, 1.0, 3.0]])),
+    ('=', glyph(&[[0.0, 1.5, 2.0, 1.5], [0.0, 2.5, 2.0, 2.5]])),
+    ('*', glyph(&[[1.0, 1.0, 1.0, 3.0], [0.0, 1.5, 2.0, 2.5], [0.0, 2.5, 2.0, 1.5]])),
+    ('/', glyph(&[[2.0, 0.0, 0.0, 4.0]])),
+    ('_', glyph(&[[0.0, 4.0, 2.0, 4.0]])),
+    ('<', glyph(&[[2.0, 0.5, 0.0, 2.0], [0.0, 2.0, 2.0, 3.5]])),
+    ('>', glyph(&[[0.0, 0.5, 2.0, 2.0], [2.0, 2.0, 0.0, 3.5]])),
+    ('(', glyph(&[[1.5, 0.0, 0.5, 1.0], [0.5, 1.0, 0.5, 3.0], [0.5, 3.0, 1.5, 4.0]])),
+    (')', glyph(&[[0.5, 0.0, 1.5, 1.0], [1.5, 1.0, 1.5, 3.0], [1.5, 3.0, 0.5, 4.0]])),
+    ('\'', glyph(&[[1.0, 0.0, 1.0, 1.0]])),
+    ('"', glyph(&[[0.5, 0.0, 0.5, 1.0], [1.5, 0.0, 1.5, 1.0]])),
+];
+
+/// Looks up the glyph for `c`. Lowercase letters use their uppercase glyph
+/// and characters the font doesn't cover are drawn as `?`.
+pub fn glyph_for(c: char) -> &'static Glyph {
+    let c = c.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(g, _)| *g == c)
+        .or_else(|| GLYPHS.iter().find(|(g, _)| *g == '?'))
+        .map(|(_, glyph)| glyph)
+        .expect("font has a '?' glyph")
+}
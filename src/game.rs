@@ -1,253 +1,960 @@
 use instant::Instant;
-use rand::{thread_rng, Rng};
-use std::{
-    f32::consts::PI,
-};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use std::{f32::consts::PI, ops::Range};
 
 use crate::{
-    draw::{draw_text, LineHandler, Point},
+    collision,
+    config::{AsteroidConfig, GameConfig},
+    draw::{
+        draw_text_styled, Align, Color, LineHandler, Point, TextStyle, CYAN, GREEN, GREY, ORANGE,
+        RED, WHITE, YELLOW,
+    },
+    entity::{Id, Store},
+    feedback::Feedback,
+    grid::Grid,
+    particles::Particles,
+    replay::{tuning_fingerprint, Event, Input, Replay},
+    scores::{self, Entry, HighScores, InitialsEntry},
+    waves::{Mix, Pattern, Wave, Waves},
     HEIGHT, WIDTH,
 };
 
+/// Number of fixed simulation ticks per second.
+pub const TICK_RATE: u128 = 120;
+
+/// Length of one simulation tick in seconds.
+const TICK_DT: f32 = 1.0 / TICK_RATE as f32;
+
+/// Longest frame `draw` will simulate, so a stalled or backgrounded window
+/// doesn't have to catch up on seconds of ticks at once.
+const MAX_FRAME_MS: u128 = 250;
+
+/// Size of the cells asteroids are bucketed into for collision checks, about
+/// the size of small ones, so each laser only looks at the asteroids right
+/// around it even when they crowd together.
+const GRID_CELL_SIZE: f32 = 20.0;
+
+/// How much further round each asteroid of a sweeping wave comes from, in radians.
+const SWEEP_STEP: f32 = PI / 7.0;
+
+/// How long the score flashes after an asteroid is destroyed, in milliseconds.
+const FLASH_MS: u128 = 150;
+
+/// Fraction of the cursor's distance from the centre the camera pans by.
+const CURSOR_PAN: f32 = 0.04;
+
+/// How quickly the zoom eases towards its target, per second.
+const ZOOM_EASING: f32 = 1.5;
+
 pub struct Game {
+    /// Lines of the playfield, seen through [`Game::camera`].
     pub lines: LineHandler,
+    /// Lines of the score, timer and messages, drawn over the playfield
+    /// without moving with the camera.
+    pub hud: LineHandler,
+    /// Shake, hit-stop and flash played in response to `events`.
+    pub feedback: Feedback,
     cur_x: u32,
     cur_y: u32,
     cur_angle: f32,
     program_begin: Instant,
-    start_time: u128,
+    last_frame_ms: u128,
+    current_tick: u128,
+    // leftover frame time, in milliseconds scaled by `TICK_RATE`
+    accumulator: u128,
+    // tick the current run started at, which it's timed from
+    start_tick: u128,
     current_ms: u128,
+    // when the game entered its current state
+    state_since: u128,
     game_time: u128,
-    last_asteroid_time: u128,
-    asteroid_spawn_rate: u128,
+    config: GameConfig,
+    waves: Waves,
+    progress: WaveProgress,
     planet_size: f32,
-    distance: f32,
-    lasers: Vec<Laser>,
-    asteroids: Vec<Asteroid>,
+    zoom: f32,
+    lasers: Store<Laser>,
+    asteroids: Store<Asteroid>,
+    // scratch space for `check_collision`, kept to reuse the allocations:
+    // every asteroid's outline in the playfield, with the corners of them
+    // all, and a grid of indices into them along with the box around each
+    // outline, to rule most out without looking them up
+    colliders: Vec<Collider>,
+    collider_points: Vec<Point>,
+    asteroid_grid: Grid<(usize, Point, Point)>,
+    spent_lasers: Vec<Id<Laser>>,
+    destroyed: Vec<Asteroid>,
+    particles: Particles,
     asteroids_destroyed: u32,
+    score: u32,
     lives: u32,
-    is_game_over: bool,
+    state: GameState,
+    last_asteroid_destroyed: Option<u128>,
+    high_scores: HighScores,
+    // where the last run placed in `high_scores`, if it made it in
+    new_high_score: Option<usize>,
+    // whether `high_scores` changed since it was last taken to be saved
+    high_scores_changed: bool,
+    // the initials it's being signed with, until they're confirmed
+    initials: Option<InitialsEntry>,
+    // emitted by the simulation since they were last drained
+    events: Vec<GameEvent>,
+    seed: u64,
+    // seed of the current run, or the last one outside of a run
+    run_seed: u64,
+    // runs started so far
+    runs: u32,
+    rng: StdRng,
+    recording: Option<Replay>,
 }
 
 impl Game {
     pub fn new() -> Self {
-        let program_begin = Instant::now();
-        let start_time = Instant::now().duration_since(program_begin).as_millis();
+        Self::with_seed(thread_rng().gen())
+    }
+
+    /// Creates a game whose randomness is fully determined by `seed`, so the
+    /// same seed and inputs always play out identically.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_waves(seed, Waves::default())
+    }
+
+    /// Like [`Game::with_seed`], playing `waves` instead of the built in ones.
+    pub fn with_waves(seed: u64, waves: Waves) -> Self {
+        Self::with_tuning(seed, GameConfig::default(), waves)
+    }
 
+    /// Like [`Game::with_seed`], tuned with `config` and playing `waves`.
+    /// Replays only play back the same with the same tuning.
+    pub fn with_tuning(seed: u64, config: GameConfig, waves: Waves) -> Self {
         Self {
             lines: LineHandler::new(),
+            hud: LineHandler::new(),
+            feedback: Feedback::default(),
             cur_x: HEIGHT / 2,
             cur_y: WIDTH / 2,
             cur_angle: 0.0,
-            program_begin,
-            start_time,
-            current_ms: start_time,
+            program_begin: Instant::now(),
+            last_frame_ms: 0,
+            current_tick: 0,
+            accumulator: 0,
+            start_tick: 0,
+            current_ms: 0,
+            state_since: 0,
             game_time: 0,
-            last_asteroid_time: start_time,
-            asteroid_spawn_rate: 1000,
-            planet_size: 100.0,
-            distance: 30.0,
-            lasers: Vec::new(),
-            asteroids: Vec::new(),
+            progress: WaveProgress::new(0, waves.wave(0), 0),
+            waves,
+            planet_size: config.planet.radius,
+            zoom: 1.0,
+            lasers: Store::new(),
+            asteroids: Store::new(),
+            colliders: Vec::new(),
+            collider_points: Vec::new(),
+            asteroid_grid: Grid::new(
+                Point { x: 0.0, y: 0.0 },
+                Point {
+                    x: WIDTH as f32,
+                    y: HEIGHT as f32,
+                },
+                GRID_CELL_SIZE,
+            ),
+            spent_lasers: Vec::new(),
+            destroyed: Vec::new(),
+            // kept apart from `rng` so effects never change the simulation
+            particles: Particles::new(seed.rotate_left(32)),
             asteroids_destroyed: 0,
-            lives: 5,
-            is_game_over: false,
+            score: 0,
+            lives: config.lives,
+            state: GameState::Title,
+            last_asteroid_destroyed: None,
+            high_scores: HighScores::default(),
+            new_high_score: None,
+            high_scores_changed: false,
+            initials: None,
+            events: Vec::new(),
+            seed,
+            run_seed: seed,
+            runs: 0,
+            rng: StdRng::seed_from_u64(seed),
+            recording: None,
+            config,
         }
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Seed of the current run, or of the last one when not in a run. A game
+    /// created with this seed and started plays the run out the same given
+    /// the same inputs at the same ticks from its start.
+    pub fn run_seed(&self) -> u64 {
+        self.run_seed
+    }
+
+    pub fn current_tick(&self) -> u128 {
+        self.current_tick
+    }
+
+    /// Starts recording every input into a [`Replay`]. Only a game that hasn't
+    /// ticked yet can be reproduced from its seed, so this must be called
+    /// before the first tick.
+    pub fn start_recording(&mut self) {
+        assert_eq!(self.current_tick, 0, "recording must start before the first tick");
+        self.recording = Some(Replay::new(
+            self.seed,
+            tuning_fingerprint(&self.config, &self.waves),
+        ));
+    }
+
+    /// Stops recording if the tuning no longer matches what the replay was
+    /// started with, as a replay is only played back with the one.
+    fn check_recording_tuning(&mut self) {
+        let tuning = tuning_fingerprint(&self.config, &self.waves);
+        if self.recording.as_ref().is_some_and(|r| r.tuning != tuning) {
+            log::warn!("The tuning changed, so the replay stops recording here");
+            self.recording = None;
+        }
+    }
+
+    /// Stops recording and returns everything recorded up to the current tick.
+    pub fn take_replay(&mut self) -> Option<Replay> {
+        let mut replay = self.recording.take()?;
+        replay.end_tick = self.current_tick;
+        Some(replay)
+    }
+
+    fn record(&mut self, input: Input) {
+        if let Some(replay) = &mut self.recording {
+            replay.events.push(Event {
+                tick: self.current_tick,
+                input,
+            });
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Milliseconds of game time since the game entered its current state.
+    pub fn time_in_state(&self) -> u128 {
+        self.current_ms - self.state_since
+    }
+
+    /// Starts a run from the title screen.
+    pub fn start(&mut self) {
+        self.record(Input::Start);
+        // not a way to resume, which would also be allowed
+        if self.state == GameState::Title {
+            self.set_state(GameState::Playing);
+        }
+    }
+
+    /// Abandons whatever is going on and starts a new run, by way of the
+    /// title screen.
     pub fn restart(&mut self) {
-        self.is_game_over = false;
-        self.start_time = self.current_ms;
+        self.record(Input::Restart);
+        self.set_state(GameState::Title);
+        self.set_state(GameState::Playing);
+    }
+
+    /// Leaves the run, or what's left of it, for the title screen.
+    pub fn return_to_title(&mut self) {
+        self.record(Input::ReturnToTitle);
+        self.set_state(GameState::Title);
+    }
+
+    /// Freezes the run until [`Game::resume`]. A paused game doesn't tick, so
+    /// pausing leaves no trace in replays and isn't recorded.
+    pub fn pause(&mut self) {
+        self.set_state(GameState::Paused);
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == GameState::Paused {
+            self.set_state(GameState::Playing);
+        }
+    }
+
+    /// Moves to `next` if the state machine allows it, running the hooks for
+    /// leaving the current state and entering the new one.
+    fn set_state(&mut self, next: GameState) {
+        let previous = self.state;
+        if !previous.can_become(next) {
+            return;
+        }
+        self.exit_state(previous);
+        self.state = next;
+        self.state_since = self.current_ms;
+        self.enter_state(next, previous);
+    }
+
+    fn exit_state(&mut self, state: GameState) {
+        if state == GameState::GameOver {
+            // the run was saved unsigned when it ended
+            self.initials = None;
+        }
+    }
+
+    fn enter_state(&mut self, state: GameState, previous: GameState) {
+        match state {
+            GameState::Title => self.reset_run(),
+            GameState::Playing if previous == GameState::Title => {
+                // every run is played from a seed of its own, so the one kept
+                // with its high score reproduces it; the first run uses the
+                // game's seed and later ones follow on from it
+                self.run_seed = if self.runs == 0 {
+                    self.seed
+                } else {
+                    self.rng.gen()
+                };
+                self.runs += 1;
+                self.rng = StdRng::seed_from_u64(self.run_seed);
+                self.start_tick = self.current_tick;
+                // the config may have changed since the game was created or
+                // last went back to the title
+                self.lives = self.config.lives;
+                self.progress = WaveProgress::new(0, self.waves.wave(0), 0);
+            }
+            GameState::Playing | GameState::Paused => {}
+            GameState::GameOver => {
+                // whatever's left breaks up, leaving the planet in peace
+                for asteroid in self.asteroids.values() {
+                    self.particles.debris(
+                        asteroid.loc,
+                        &asteroid.edges(1.0),
+                        asteroid.vx,
+                        asteroid.vy,
+                        GREY,
+                    );
+                }
+                self.asteroids.clear();
+                self.lasers.clear();
+                self.end_run();
+            }
+        }
+    }
+
+    /// Enters the run that just ended into the high-score table, asking for
+    /// initials to sign it with if it made it in.
+    fn end_run(&mut self) {
+        self.new_high_score = self.high_scores.insert(Entry {
+            score: self.score,
+            time: self.game_time as u64,
+            date: scores::now(),
+            seed: self.run_seed,
+            initials: None,
+        });
+        if self.new_high_score.is_some() {
+            self.high_scores_changed = true;
+            self.initials = Some(InitialsEntry::new());
+        }
+        self.events.push(GameEvent::GameOver);
+    }
+
+    /// Clears away everything from the last run, ready for the next.
+    fn reset_run(&mut self) {
+        self.start_tick = self.current_tick;
         self.game_time = 0;
-        self.lives = 5;
+        self.lives = self.config.lives;
         self.asteroids_destroyed = 0;
-        self.asteroids.clear();
+        self.score = 0;
+        // fresh stores rather than cleared ones, so entities fill slots in
+        // the same order as in a new game
+        self.asteroids = Store::new();
+        self.lasers = Store::new();
+        self.particles.clear();
+        self.last_asteroid_destroyed = None;
+        self.new_high_score = None;
+        self.progress = WaveProgress::new(0, self.waves.wave(0), 0);
+    }
+
+    /// Swaps in new waves, which take over from the wave currently playing.
+    pub fn set_waves(&mut self, waves: Waves) {
+        self.waves = waves;
+        self.progress.wave = self.waves.wave(self.progress.number);
+        // the new wave may be smaller than what's already been spawned
+        self.progress.spawned = self.progress.spawned.min(self.progress.wave.asteroids);
+        self.check_recording_tuning();
+    }
+
+    /// Swaps in new tuning, which applies straight away except for the number
+    /// of lives, which only changes when the next run starts. A replay being recorded stops
+    /// if the tuning changes, as it couldn't be played back.
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+        self.check_recording_tuning();
+    }
+
+    /// Swaps in the high-score table runs are entered into, usually one loaded
+    /// with [`HighScores::load`]. Games start with an empty one.
+    pub fn set_high_scores(&mut self, high_scores: HighScores) {
+        self.high_scores = high_scores;
+    }
+
+    pub fn high_scores(&self) -> &HighScores {
+        &self.high_scores
+    }
+
+    /// Whether the high-score table changed since the last call, and so needs
+    /// saving. The game never saves it itself, so headless games leave the
+    /// player's table alone.
+    pub fn take_high_scores_changed(&mut self) -> bool {
+        std::mem::take(&mut self.high_scores_changed)
+    }
+
+    /// The initials a new high score is being signed with, while the player
+    /// is entering them. None of the usual inputs should reach the game then,
+    /// but they're only held back by the caller, so replays still play back
+    /// the same whatever the high-score table holds.
+    pub fn initials(&self) -> Option<&InitialsEntry> {
+        self.initials.as_ref()
+    }
+
+    pub fn initials_mut(&mut self) -> Option<&mut InitialsEntry> {
+        self.initials.as_mut()
+    }
+
+    /// Signs the new high score with the initials entered, leaving the table
+    /// to be saved, see [`Game::take_high_scores_changed`].
+    pub fn confirm_initials(&mut self) {
+        if let (Some(initials), Some(rank)) = (self.initials.take(), self.new_high_score) {
+            self.high_scores.sign(rank, initials.text());
+            self.high_scores_changed = true;
+        }
+    }
+
+    /// Number of the wave being played or about to start, counting from 1.
+    pub fn wave_number(&self) -> usize {
+        self.progress.number + 1
     }
 
     pub fn set_cursor(&mut self, x: u32, y: u32) {
+        self.record(Input::Cursor { x, y });
         self.cur_x = x;
         self.cur_y = y;
         let local_x = self.cur_x as f32 - (WIDTH / 2) as f32;
-        let local_y = -1.0 * (self.cur_y as f32 - (HEIGHT / 2) as f32);
+        let local_y = -(self.cur_y as f32 - (HEIGHT / 2) as f32);
         self.cur_angle = local_y.atan2(local_x);
     }
 
     pub fn fire(&mut self) {
-        self.lasers.push(Laser {
-            loc: Point {
-                x: (self.distance + 30.0 + self.planet_size) * self.cur_angle.cos()
-                    + (WIDTH / 2) as f32,
-                y: -(self.distance + 30.0 + self.planet_size) * self.cur_angle.sin()
-                    + (HEIGHT / 2) as f32,
-            },
-            vx: 500.0 * self.cur_angle.cos(),
-            vy: -500.0 * self.cur_angle.sin(),
+        if self.state != GameState::Playing {
+            return;
+        }
+        self.record(Input::Fire);
+        // from the nose of the ship
+        let reach = self.planet_size + self.config.ship_distance + self.config.ship_length;
+        let loc = Point {
+            x: reach * self.cur_angle.cos() + (WIDTH / 2) as f32,
+            y: -reach * self.cur_angle.sin() + (HEIGHT / 2) as f32,
+        };
+
+        let speed = self.config.laser_speed;
+        self.lasers.insert(Laser {
+            loc,
+            prev_loc: loc,
+            vx: speed * self.cur_angle.cos(),
+            vy: -speed * self.cur_angle.sin(),
         });
     }
 
+    /// Advances the simulation by the wall-clock time since the last frame and
+    /// redraws it into `lines`.
     pub fn draw(&mut self) {
-        self.lines.clear_lines();
-        let new_time = Instant::now().duration_since(self.program_begin).as_millis();
-        let dt = new_time - self.current_ms;
-        self.current_ms = new_time;
-
-        if self.lives <= 0 {
-            self.is_game_over = true;
-        }
-
-        if !self.is_game_over {
-            self.game_time = self.current_ms - self.start_time;
-        }
-
-        if self.asteroids_destroyed < 30 {
-            self.asteroid_spawn_rate = 2000;
-        } else if self.asteroids_destroyed < 60 {
-            self.asteroid_spawn_rate = 1500;
-        } else if self.asteroids_destroyed < 80 {
-            self.asteroid_spawn_rate = 1200;
-        } else if self.asteroids_destroyed < 100 {
-            self.asteroid_spawn_rate = 1000;
-        } else if self.asteroids_destroyed < 120 {
-            self.asteroid_spawn_rate = 800;
-        } else if self.asteroids_destroyed < 150 {
-            self.asteroid_spawn_rate = 600;
-        } else {
-            self.asteroid_spawn_rate = 400;
+        let now = Instant::now().duration_since(self.program_begin).as_millis();
+        let dt = (now - self.last_frame_ms).min(MAX_FRAME_MS);
+        self.last_frame_ms = now;
+
+        let dt = self.feedback.advance(dt);
+        self.step(dt);
+        for event in self.events.drain(..) {
+            self.feedback.trigger(event);
         }
+        self.render();
+    }
+
+    /// Advances the simulation by `dt` milliseconds without touching `lines`,
+    /// so games can be run headless. Time is consumed in fixed ticks of
+    /// `1 / TICK_RATE` seconds and any remainder is carried over to the next
+    /// call, so the outcome doesn't depend on how `dt` is split up.
+    pub fn step(&mut self, dt: u128) {
+        // keeping the accumulator as it was keeps moving objects still too
+        if self.state == GameState::Paused {
+            return;
+        }
+        self.accumulator += dt * TICK_RATE;
+        while self.accumulator >= 1000 {
+            self.accumulator -= 1000;
+            self.tick();
+        }
+    }
+
+    /// Milliseconds since the current run started. Everything in a run is
+    /// timed by this rather than `current_ms`, counted in whole ticks so a
+    /// run plays out the same whichever tick it starts on.
+    fn run_ms(&self) -> u128 {
+        (self.current_tick - self.start_tick) * 1000 / TICK_RATE
+    }
+
+    /// Advances the simulation by exactly one fixed tick, unless it's paused.
+    pub fn tick(&mut self) {
+        if self.state == GameState::Paused {
+            return;
+        }
+        self.current_tick += 1;
+        self.current_ms = self.current_tick * 1000 / TICK_RATE;
+
+        if self.state == GameState::Playing {
+            self.game_time = self.run_ms();
+            self.update_wave();
+        }
+
+        let planet = &self.config.planet;
+        let run_ms = self.run_ms();
+        self.planet_size = planet.radius + planet.pulse * (run_ms as f64 / 500.0).sin() as f32;
+
+        // zoom out a little further every wave until 0.8 at wave 11, so the
+        // asteroids are seen coming from further away as they get busier
+        let intensity = self.progress.number as f32 / 10.0;
+        let target_zoom = 1.0 - 0.2 * intensity.min(1.0);
+        self.zoom += (target_zoom - self.zoom) * (1.0 - (-ZOOM_EASING * TICK_DT).exp());
 
-        if !self.is_game_over
-            && self.current_ms - self.last_asteroid_time > self.asteroid_spawn_rate
-        {
-            self.add_asteroid(20.0);
-            self.last_asteroid_time = self.current_ms;
+        self.update_lasers();
+        self.update_asteroids();
+        self.check_collision();
+        self.particles.update(TICK_DT);
+
+        if self.state == GameState::Playing && self.lives == 0 {
+            self.set_state(GameState::GameOver);
         }
+    }
 
-        if self.is_game_over {
-            self.draw_game_over();
+    /// Redraws the current state of the simulation into `lines`, interpolating
+    /// moving objects between the last two ticks by the time left over in the
+    /// accumulator.
+    pub fn render(&mut self) {
+        self.lines.clear_lines();
+        self.hud.clear_lines();
+
+        if self.state == GameState::GameOver {
+            self.lines.push_color(ORANGE);
+            self.draw_face();
+            self.lines.pop_color();
         }
         self.draw_ship();
+
+        let planet_color = if self.feedback.planet_flash() { RED } else { CYAN };
+        self.lines.push_color(planet_color);
         self.draw_planet();
-        self.draw_lasers(dt);
+        self.lines.pop_color();
+
+        let alpha = self.accumulator as f32 / 1000.0;
+        self.lines.push_color(GREEN);
+        self.draw_lasers(alpha);
+        self.lines.pop_color();
+
+        self.lines.push_color(GREY);
+        self.draw_asteroids(alpha);
+        self.lines.pop_color();
+
+        self.particles.draw(&mut self.lines, alpha);
+
+        // every screen puts its own text over the playfield
+        match self.state {
+            GameState::Title => self.draw_title(),
+            GameState::Playing => {
+                self.draw_status();
+                self.draw_wave_banner();
+            }
+            GameState::Paused => {
+                self.draw_status();
+                self.draw_paused();
+            }
+            GameState::GameOver => {
+                self.draw_status();
+                self.hud.push_color(ORANGE);
+                self.draw_game_over();
+                self.hud.pop_color();
+            }
+        }
+    }
+
+    /// Survival time, score and lives along the top of the screen.
+    fn draw_status(&mut self) {
         self.draw_text(
             &format!("{:.2}", self.game_time as f64 / 1000.0),
             10.0,
             10.0,
+            &TextStyle::default(),
+        );
+        let score_color = self.flash_color(self.last_asteroid_destroyed, WHITE, YELLOW);
+        self.hud.push_color(score_color);
+        self.draw_text(
+            &format!("{}", self.score),
+            WIDTH as f32 / 2.0,
+            10.0,
+            &TextStyle {
+                align: Align::Center,
+                ..Default::default()
+            },
         );
-        self.draw_text(&format!("{}", self.asteroids_destroyed), 500.0, 10.0);
+        self.hud.pop_color();
+
+        self.hud.push_color(RED);
         self.draw_hearts(self.lives, WIDTH as f32 - 10.0, 10.0);
-        self.draw_asteroids(dt);
-        self.check_collision();
+        self.hud.pop_color();
+    }
+
+    /// Announces the next wave while it's in its intermission.
+    fn draw_wave_banner(&mut self) {
+        if !self.progress.in_intermission(self.run_ms()) {
+            return;
+        }
+        let banner = TextStyle {
+            size: 50.0,
+            align: Align::Center,
+            letter_spacing: 8.0,
+        };
+        self.hud.push_color(CYAN);
+        self.draw_text(
+            &format!("WAVE {}", self.wave_number()),
+            WIDTH as f32 / 2.0,
+            320.0,
+            &banner,
+        );
+        self.hud.pop_color();
+    }
+
+    fn draw_title(&mut self) {
+        let title = TextStyle {
+            align: Align::Center,
+            letter_spacing: 10.0,
+            ..Default::default()
+        };
+        let hint = TextStyle {
+            size: 30.0,
+            align: Align::Center,
+            ..Default::default()
+        };
+        self.hud.push_color(CYAN);
+        self.draw_text("PLANET DEFENDER", WIDTH as f32 / 2.0, 180.0, &title);
+        self.hud.pop_color();
+        self.draw_text("CLICK TO START", WIDTH as f32 / 2.0, 250.0, &hint);
+
+        self.draw_high_scores(650.0);
+    }
+
+    fn draw_paused(&mut self) {
+        let title = TextStyle {
+            align: Align::Center,
+            letter_spacing: 10.0,
+            ..Default::default()
+        };
+        let hint = TextStyle {
+            size: 30.0,
+            align: Align::Center,
+            ..Default::default()
+        };
+        self.draw_text("PAUSED", WIDTH as f32 / 2.0, 180.0, &title);
+        self.draw_text("PRESS P TO RESUME", WIDTH as f32 / 2.0, 250.0, &hint);
+    }
+
+    /// `flash` for a short while after the event at `since`, `normal` otherwise.
+    fn flash_color(&self, since: Option<u128>, normal: Color, flash: Color) -> Color {
+        match since {
+            Some(t) if self.current_ms - t < FLASH_MS => flash,
+            _ => normal,
+        }
+    }
+
+    /// Where the playfield should be seen from: zoomed out as the waves
+    /// intensify, panned slightly towards the cursor and shaking for a moment
+    /// after the planet is hit.
+    pub fn camera(&self) -> Camera {
+        let (half_width, half_height) = (WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+        let shake = self.feedback.shake_offset();
+        Camera {
+            center: Point {
+                x: half_width + (self.cur_x as f32 - half_width) * CURSOR_PAN + shake.x,
+                y: half_height + (self.cur_y as f32 - half_height) * CURSOR_PAN + shake.y,
+            },
+            zoom: self.zoom,
+        }
+    }
+
+    /// Takes the events the simulation emitted since the last call. `draw`
+    /// already passes them on to `feedback`; headless callers of `step` or
+    /// `tick` can use this to react to them instead.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    /// Captures the observable state of the simulation.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            tick: self.current_tick,
+            game_time: self.game_time,
+            wave: self.wave_number(),
+            asteroids_destroyed: self.asteroids_destroyed,
+            score: self.score,
+            lives: self.lives,
+            state: self.state,
+            cursor_angle: self.cur_angle,
+            planet_size: self.planet_size,
+            asteroids: self.asteroids.values().map(|a| a.loc).collect(),
+            lasers: self.lasers.values().map(|l| l.loc).collect(),
+        }
     }
 
     fn check_collision(&mut self) {
-        let mut a = 0;
-        while a < self.asteroids.len() {
-            let asteroid = &self.asteroids[a];
-
-            let mut collides_with_laser = false;
-            let mut l = 0;
-            while l < self.lasers.len() {
-                let laser = &self.lasers[l];
-                if distance(laser.loc, asteroid.loc) < 20.0 {
-                    collides_with_laser = true;
-                    if !self.is_game_over {
-                        self.asteroids_destroyed += 1;
-                    }
+        if self.lasers.is_empty() {
+            return;
+        }
+
+        // move every outline into the playfield once, rather than for each
+        // laser that comes near it, and bucket them by their bounds grown by
+        // as far as any laser moves in a tick, so a laser whose path this
+        // tick touches an asteroid finds it in the cell its tip ended up in
+        let step = self
+            .lasers
+            .values()
+            .map(|laser| laser.vx.hypot(laser.vy) * TICK_DT)
+            .fold(0.0, f32::max);
+        self.colliders.clear();
+        self.collider_points.clear();
+        self.asteroid_grid.clear();
+        for (id, asteroid) in self.asteroids.iter() {
+            let start = self.collider_points.len();
+            self.collider_points.extend(asteroid.points(1.0));
+            let points = start..self.collider_points.len();
+            let (min, max) = bounds(&self.collider_points[points.clone()]);
+            let grown_min = Point {
+                x: min.x - step,
+                y: min.y - step,
+            };
+            let grown_max = Point {
+                x: max.x + step,
+                y: max.y + step,
+            };
+            self.asteroid_grid
+                .insert((self.colliders.len(), min, max), grown_min, grown_max);
+            self.colliders.push(Collider {
+                id,
+                center: asteroid.loc,
+                radius: asteroid.bounding_radius,
+                points,
+                destroyed: false,
+            });
+        }
+
+        self.spent_lasers.clear();
+        for (l, laser) in self.lasers.iter() {
+            let (path_min, path_max) = bounds(&[laser.prev_loc, laser.loc]);
+            for &(i, min, max) in self.asteroid_grid.at(laser.loc) {
+                // most are nowhere near, which is cheap to rule out
+                if path_min.x > max.x
+                    || path_max.x < min.x
+                    || path_min.y > max.y
+                    || path_max.y < min.y
+                {
+                    continue;
+                }
+                // gone if an earlier laser already destroyed it
+                let collider = &mut self.colliders[i];
+                if collider.destroyed {
+                    continue;
+                }
+                // lasers only grazing the box usually pass the outline by too
+                let distance =
+                    collision::distance_to_segment(collider.center, laser.prev_loc, laser.loc);
+                if distance > collider.radius {
+                    continue;
+                }
+
+                // sweep the tip over everything it passed through this tick
+                // so fast lasers can't skip over small asteroids
+                let polygon = &self.collider_points[collider.points.clone()];
+                if collision::segment_intersects_polygon(laser.prev_loc, laser.loc, polygon) {
+                    // each laser destroys at most one asteroid
+                    collider.destroyed = true;
+                    self.spent_lasers.push(l);
+                    self.destroyed.extend(self.asteroids.remove(collider.id));
                     break;
-                } else {
-                    l += 1;
                 }
             }
+        }
 
-            if collides_with_laser {
-                // remove asteroid + laser
-                self.asteroids.remove(a);
-                self.lasers.remove(l);
-            } else {
-                a += 1;
-            }
+        for &l in &self.spent_lasers {
+            self.lasers.remove(l);
         }
 
-        fn distance(pt1: Point, pt2: Point) -> f32 {
-            ((pt2.x - pt1.x).powf(2.0) + (pt2.y - pt1.y).powf(2.0)).sqrt()
+        let mut destroyed = std::mem::take(&mut self.destroyed);
+        for asteroid in destroyed.drain(..) {
+            if self.state == GameState::Playing {
+                self.asteroids_destroyed += 1;
+                self.score += self.config.asteroids.get(asteroid.size).score;
+                self.last_asteroid_destroyed = Some(self.current_ms);
+                self.events.push(GameEvent::AsteroidDestroyed);
+            }
+            // leave its edges behind as debris
+            self.particles.debris(
+                asteroid.loc,
+                &asteroid.edges(1.0),
+                asteroid.vx,
+                asteroid.vy,
+                GREY,
+            );
+            self.split_asteroid(&asteroid);
         }
+        // handing back the allocation
+        self.destroyed = destroyed;
     }
 
-    fn add_asteroid(&mut self, size: f32) {
-        let quadrant = [
-            rand::thread_rng().gen_range(0.0..=(PI / 2.0)),
-            rand::thread_rng().gen_range((PI / 2.0)..=PI),
-            rand::thread_rng().gen_range(PI..=(3.0 * PI / 2.0)),
-            rand::thread_rng().gen_range((3.0 * PI / 2.0)..=(2.0 * PI)),
-        ];
+    /// Spawns the current wave's asteroids as they come due, and starts the
+    /// next wave once the current one is cleared.
+    fn update_wave(&mut self) {
+        let now = self.run_ms();
+        let progress = &self.progress;
+        if now < progress.next_spawn {
+            return;
+        }
 
-        let angle = rand::thread_rng().gen_range(0.0..(2.0 * PI));
-        let distance = 707.0;
+        let remaining = progress.wave.asteroids.saturating_sub(progress.spawned);
+        if remaining > 0 {
+            let group = progress.wave.group.min(remaining);
+            let interval = (progress.wave.spawn_interval * 1000.0) as u128;
+            match progress.wave.pattern {
+                Pattern::Random => {
+                    for _ in 0..group {
+                        let angle = self.rng.gen_range(0.0..(2.0 * PI));
+                        self.spawn_asteroid(angle);
+                    }
+                }
+                Pattern::Sweep => {
+                    for _ in 0..group {
+                        self.progress.sweep_angle += SWEEP_STEP;
+                        self.spawn_asteroid(self.progress.sweep_angle);
+                    }
+                }
+                Pattern::Ring => {
+                    let start = self.rng.gen_range(0.0..(2.0 * PI));
+                    for i in 0..group {
+                        self.spawn_asteroid(start + i as f32 * 2.0 * PI / group as f32);
+                    }
+                }
+            }
+            self.progress.spawned += group;
+            self.progress.next_spawn = now + interval;
+        } else if self.asteroids.is_empty() {
+            let number = self.progress.number + 1;
+            self.progress = WaveProgress::new(number, self.waves.wave(number), now);
+            self.progress.sweep_angle = self.rng.gen_range(0.0..(2.0 * PI));
+        }
+    }
 
-        let x = distance * angle.cos() + (WIDTH as f32 / 2.0);
-        let y = distance * angle.sin() + (HEIGHT as f32 / 2.0);
+    /// Spawns an asteroid from the current wave's mix just outside the
+    /// playfield, heading for the planet from a random direction. The game
+    /// spawns them on its own as it ticks, calling this directly is mostly
+    /// useful for stress tests.
+    pub fn add_asteroid(&mut self) {
+        let angle = self.rng.gen_range(0.0..(2.0 * PI));
+        self.spawn_asteroid(angle);
+    }
 
-        let velocity = rand::thread_rng().gen_range(50.0..150.0);
+    /// Spawns an asteroid from the current wave's mix at `angle` around the
+    /// planet, just outside the playfield and heading for the planet.
+    fn spawn_asteroid(&mut self, angle: f32) {
+        let Mix {
+            large,
+            medium,
+            small,
+        } = self.progress.wave.mix;
+        let pick = self.rng.gen_range(0.0..(large + medium + small));
+        let size = if pick < large {
+            AsteroidSize::Large
+        } else if pick < large + medium {
+            AsteroidSize::Medium
+        } else {
+            AsteroidSize::Small
+        };
 
-        let vx = -velocity * angle.cos();
-        let vy = -velocity * angle.sin();
+        let distance = self.config.spawn_distance + self.config.asteroids.get(size).radius;
 
-        let p1 = Point {
-            x: size * quadrant[0].cos(),
-            y: -size * quadrant[0].sin(),
-        };
-        let p2 = Point {
-            x: size * quadrant[1].cos(),
-            y: -size * quadrant[1].sin(),
-        };
-        let p3 = Point {
-            x: size * quadrant[2].cos(),
-            y: -size * quadrant[2].sin(),
-        };
-        let p4 = Point {
-            x: size * quadrant[3].cos(),
-            y: -size * quadrant[3].sin(),
+        let loc = Point {
+            x: distance * angle.cos() + (WIDTH as f32 / 2.0),
+            y: distance * angle.sin() + (HEIGHT as f32 / 2.0),
         };
+        let asteroid = self.create_asteroid(size, loc, angle + PI);
+        self.asteroids.insert(asteroid);
+    }
 
-        self.asteroids.push(Asteroid {
-            loc: Point { x, y },
-            p1: Point {
-                x: p1.x + x,
-                y: p1.y + y,
-            },
-            p2: Point {
-                x: p2.x + x,
-                y: p2.y + y,
-            },
-            p3: Point {
-                x: p3.x + x,
-                y: p3.y + y,
-            },
-            p4: Point {
-                x: p4.x + x,
-                y: p4.y + y,
-            },
-            vx,
-            vy,
-            rotation_speed: PI
-                / (if rand::thread_rng().gen_bool(0.5) {
+    /// Creates an asteroid at `loc` moving in the direction `heading`, with a
+    /// random outline, speed and spin.
+    fn create_asteroid(&mut self, size: AsteroidSize, loc: Point, heading: f32) -> Asteroid {
+        let (slowest, fastest) = self.progress.wave.speed;
+        let &AsteroidConfig { radius, speed, .. } = self.config.asteroids.get(size);
+        let velocity = self.rng.gen_range(slowest..=fastest) * speed;
+        let outline = self.craggy_outline(radius);
+        let bounding_radius = outline.iter().map(|p| p.x.hypot(p.y)).fold(0.0, f32::max);
+
+        Asteroid {
+            size,
+            radius,
+            loc,
+            prev_loc: loc,
+            angle: 0.0,
+            prev_angle: 0.0,
+            outline,
+            bounding_radius,
+            vx: velocity * heading.cos(),
+            vy: velocity * heading.sin(),
+            // radians per second, matching the old per-frame speeds at 60 fps
+            rotation_speed: 60.0 * PI
+                / (if self.rng.gen_bool(0.5) {
                     -1.0
                 } else {
                     1.0
-                } * thread_rng().gen_range(200.0..500.00)),
-            // rotation_speed: PI / rand::thread_rng().gen_range(-100.0..100.0),
-        });
+                } * self.rng.gen_range(200.0..500.00)),
+        }
     }
 
-    fn asteroid_hit(&self, a: &Asteroid) -> bool {
-        let Asteroid { loc, .. } = a;
-        let Point { x, y } = loc;
+    /// Generates a jagged outline around the origin, roughly `radius` across:
+    /// evenly spread corners pushed in and out by random amounts, with every
+    /// other one biased inwards to give the rock craggy dents.
+    fn craggy_outline(&mut self, radius: f32) -> Vec<Point> {
+        let corners = self.rng.gen_range(7..=12);
+        let step = 2.0 * PI / corners as f32;
 
-        ((x - WIDTH as f32 / 2.0).powf(2.0) + (y - HEIGHT as f32 / 2.0).powf(2.0)).sqrt()
-            < self.planet_size
+        (0..corners)
+            .map(|i| {
+                let angle = i as f32 * step + self.rng.gen_range(-0.3..0.3) * step;
+                let depth = if i % 2 == 0 { 0.85..1.1 } else { 0.6..0.95 };
+                let distance = radius * self.rng.gen_range(depth);
+                Point {
+                    x: distance * angle.cos(),
+                    y: -distance * angle.sin(),
+                }
+            })
+            .collect()
     }
 
-    fn draw_game_over(&mut self) {
+    /// Replaces a destroyed asteroid with two of the next size down, veering
+    /// off either side of its heading.
+    fn split_asteroid(&mut self, asteroid: &Asteroid) {
+        if let Some(size) = asteroid.size.smaller() {
+            let heading = asteroid.vy.atan2(asteroid.vx);
+            let split_angle = self.config.split_angle.to_radians();
+            for veer in [-split_angle, split_angle] {
+                let fragment = self.create_asteroid(size, asteroid.loc, heading + veer);
+                self.asteroids.insert(fragment);
+            }
+        }
+    }
+
+    /// Frowning face drawn over the planet once it's lost.
+    fn draw_face(&mut self) {
         self.lines
             .add_line(Point { x: 460.0, y: 530.0 }, Point { x: 540.0, y: 530.0 });
         self.lines
@@ -258,75 +965,194 @@ impl Game {
             .add_line(Point { x: 460.0, y: 470.0 }, Point { x: 460.0, y: 440.0 });
         self.lines
             .add_line(Point { x: 540.0, y: 470.0 }, Point { x: 540.0, y: 440.0 });
+    }
 
-        self.lines
-            .add_line(Point { x: 480.0, y: 200.0 }, Point { x: 480.0, y: 280.0 });
-        self.lines
-            .add_line(Point { x: 480.0, y: 200.0 }, Point { x: 520.0, y: 200.0 });
-        self.lines
-            .add_line(Point { x: 520.0, y: 200.0 }, Point { x: 520.0, y: 240.0 });
-        self.lines
-            .add_line(Point { x: 520.0, y: 240.0 }, Point { x: 480.0, y: 240.0 });
-        self.lines
-            .add_line(Point { x: 480.0, y: 240.0 }, Point { x: 520.0, y: 280.0 });
-        self.lines
-            .add_line(Point { x: 460.0, y: 210.0 }, Point { x: 460.0, y: 190.0 });
-        self.lines
-            .add_line(Point { x: 450.0, y: 210.0 }, Point { x: 450.0, y: 190.0 });
-        self.lines
-            .add_line(Point { x: 540.0, y: 210.0 }, Point { x: 540.0, y: 190.0 });
-        self.lines
-            .add_line(Point { x: 550.0, y: 210.0 }, Point { x: 550.0, y: 190.0 });
+    fn draw_game_over(&mut self) {
+        let title = TextStyle {
+            align: Align::Center,
+            letter_spacing: 10.0,
+            ..Default::default()
+        };
+        let hint = TextStyle {
+            size: 30.0,
+            align: Align::Center,
+            ..Default::default()
+        };
+        let small_hint = TextStyle { size: 20.0, ..hint };
+        self.draw_text("GAME OVER", WIDTH as f32 / 2.0, 180.0, &title);
+
+        if let Some(initials) = self.initials.clone() {
+            self.draw_text("NEW HIGH SCORE", WIDTH as f32 / 2.0, 250.0, &hint);
+            self.draw_initials_entry(&initials, 650.0);
+        } else {
+            self.draw_text("PRESS R", WIDTH as f32 / 2.0, 250.0, &hint);
+            self.draw_text(
+                "OR SPACE FOR THE TITLE",
+                WIDTH as f32 / 2.0,
+                290.0,
+                &small_hint,
+            );
+            self.draw_high_scores(650.0);
+        }
     }
 
-    fn draw_asteroids(&mut self, dt: u128) {
-        let mut i = 0;
-        while i < self.asteroids.len() {
-            if self.asteroid_hit(&self.asteroids[i]) {
-                self.asteroids.remove(i);
-                if self.lives > 0 {
-                    self.lives -= 1;
-                }
-            } else {
-                self.asteroids[i].update(dt);
-
-                self.lines
-                    .add_line(self.asteroids[i].p1, self.asteroids[i].p2);
-                self.lines
-                    .add_line(self.asteroids[i].p2, self.asteroids[i].p3);
-                self.lines
-                    .add_line(self.asteroids[i].p3, self.asteroids[i].p4);
-                self.lines
-                    .add_line(self.asteroids[i].p4, self.asteroids[i].p1);
-
-                i += 1;
+    /// Draws the initials being entered from `y` down, with the letter being
+    /// picked underlined.
+    fn draw_initials_entry(&mut self, initials: &InitialsEntry, y: f32) {
+        let letter = TextStyle {
+            size: 60.0,
+            align: Align::Center,
+            ..Default::default()
+        };
+        let hint = TextStyle {
+            size: 20.0,
+            align: Align::Center,
+            ..Default::default()
+        };
+        let spacing = 60.0;
+        let first_x = WIDTH as f32 / 2.0 - spacing;
+
+        for (i, c) in initials.text().chars().enumerate() {
+            let x = first_x + i as f32 * spacing;
+            let picking = i == initials.cursor();
+            if picking {
+                self.hud.push_color(YELLOW);
+            }
+            self.draw_text(&c.to_string(), x, y, &letter);
+            if picking {
+                self.hud.add_line(
+                    Point {
+                        x: x - 20.0,
+                        y: y + 75.0,
+                    },
+                    Point {
+                        x: x + 20.0,
+                        y: y + 75.0,
+                    },
+                );
+                self.hud.pop_color();
             }
         }
+
+        self.draw_text("TYPE OR SCROLL TO PICK", WIDTH as f32 / 2.0, y + 110.0, &hint);
+        self.draw_text("ENTER TO SAVE", WIDTH as f32 / 2.0, y + 140.0, &hint);
     }
 
-    fn draw_text(&mut self, text: &str, x: f32, y: f32) {
-        draw_text(&mut self.lines, text, x, y);
+    /// Lists the high-score table from `y` down, highlighting the last run if
+    /// it made it in.
+    fn draw_high_scores(&mut self, y: f32) {
+        if self.high_scores.entries().is_empty() {
+            return;
+        }
+
+        let heading = TextStyle {
+            size: 25.0,
+            align: Align::Center,
+            letter_spacing: 5.0,
+        };
+        let left = TextStyle {
+            size: 20.0,
+            ..Default::default()
+        };
+        let right = TextStyle {
+            align: Align::Right,
+            ..left
+        };
+        self.draw_text("HIGH SCORES", WIDTH as f32 / 2.0, y, &heading);
+
+        let rows: Vec<_> = self
+            .high_scores
+            .entries()
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                (
+                    format!("{}.", rank + 1),
+                    entry.initials.clone().unwrap_or_else(|| "---".to_string()),
+                    entry.score.to_string(),
+                    format!("{:.2}", entry.time as f64 / 1000.0),
+                    scores::format_date(entry.date),
+                )
+            })
+            .collect();
+        for (rank, (place, initials, score, time, date)) in rows.iter().enumerate() {
+            let y = y + 45.0 + rank as f32 * 30.0;
+            if self.new_high_score == Some(rank) {
+                self.hud.push_color(YELLOW);
+            }
+            self.draw_text(place, 250.0, y, &right);
+            self.draw_text(initials, 280.0, y, &left);
+            self.draw_text(score, 480.0, y, &right);
+            self.draw_text(time, 620.0, y, &right);
+            self.draw_text(date, 800.0, y, &right);
+            if self.new_high_score == Some(rank) {
+                self.hud.pop_color();
+            }
+        }
+    }
+
+    fn update_asteroids(&mut self) {
+        let planet_size = self.planet_size;
+        let spawn_distance = self.config.spawn_distance;
+        let mut hits = 0;
+        self.asteroids.retain(|_, asteroid| {
+            if !asteroid.hits_planet(planet_size) {
+                asteroid.update(TICK_DT);
+                // fragments that split off the wrong way never come back
+                let (dx, dy) = (
+                    asteroid.loc.x - WIDTH as f32 / 2.0,
+                    asteroid.loc.y - HEIGHT as f32 / 2.0,
+                );
+                // well past where asteroids spawn, so only ones flying away
+                // get there
+                return dx.hypot(dy) < spawn_distance + asteroid.radius + 100.0;
+            }
+
+            // sparks fly back out from where it struck the planet
+            let angle =
+                (asteroid.loc.y - HEIGHT as f32 / 2.0).atan2(asteroid.loc.x - WIDTH as f32 / 2.0);
+            self.particles.sparks(asteroid.loc, angle, 12, ORANGE);
+            hits += 1;
+            false
+        });
+
+        for _ in 0..hits {
+            self.events.push(GameEvent::PlanetHit);
+        }
+        self.lives = self.lives.saturating_sub(hits);
+    }
+
+    fn draw_asteroids(&mut self, alpha: f32) {
+        for asteroid in self.asteroids.values() {
+            for (p1, p2) in asteroid.edges(alpha) {
+                self.lines.add_line(p1, p2);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, style: &TextStyle) {
+        draw_text_styled(&mut self.hud, text, x, y, style);
     }
 
     #[rustfmt::skip]
     fn draw_hearts(&mut self, hearts: u32, mut x: f32, y: f32) {
         for _ in 0..hearts {
-            self.lines.add_line(Point {x: x - 5.0, y}, Point {x: x - 15.0, y});
-            self.lines.add_line(Point {x: x - 15.0, y}, Point {x: x - 20.0, y: y + 5.0});
-            self.lines.add_line(Point {x: x - 20.0, y: y + 5.0}, Point {x: x - 25.0, y});
-            self.lines.add_line(Point {x: x - 25.0, y}, Point {x: x - 35.0, y});
-            self.lines.add_line(Point {x: x - 35.0, y}, Point {x: x - 40.0, y: y + 5.0});
-            self.lines.add_line(Point {x: x - 40.0, y: y + 5.0}, Point {x: x - 40.0, y: y + 20.0});
-            self.lines.add_line(Point {x: x - 40.0, y: y + 20.0}, Point {x: x - 20.0, y: y + 40.0});
-            self.lines.add_line(Point {x: x - 20.0, y: y + 40.0}, Point {x, y: y + 20.0});
-            self.lines.add_line(Point {x, y: y + 20.0}, Point {x, y: y + 5.0});
-            self.lines.add_line(Point {x, y: y + 5.0}, Point {x: x - 5.0, y});
+            self.hud.add_line(Point {x: x - 5.0, y}, Point {x: x - 15.0, y});
+            self.hud.add_line(Point {x: x - 15.0, y}, Point {x: x - 20.0, y: y + 5.0});
+            self.hud.add_line(Point {x: x - 20.0, y: y + 5.0}, Point {x: x - 25.0, y});
+            self.hud.add_line(Point {x: x - 25.0, y}, Point {x: x - 35.0, y});
+            self.hud.add_line(Point {x: x - 35.0, y}, Point {x: x - 40.0, y: y + 5.0});
+            self.hud.add_line(Point {x: x - 40.0, y: y + 5.0}, Point {x: x - 40.0, y: y + 20.0});
+            self.hud.add_line(Point {x: x - 40.0, y: y + 20.0}, Point {x: x - 20.0, y: y + 40.0});
+            self.hud.add_line(Point {x: x - 20.0, y: y + 40.0}, Point {x, y: y + 20.0});
+            self.hud.add_line(Point {x, y: y + 20.0}, Point {x, y: y + 5.0});
+            self.hud.add_line(Point {x, y: y + 5.0}, Point {x: x - 5.0, y});
 
             x -= 50.0;
         }
     }
 
-    fn draw_lasers(&mut self, dt: u128) {
+    fn update_lasers(&mut self) {
         fn laser_out_of_bounds(l: &Laser, epsilon: f32) -> bool {
             l.loc.x < -epsilon
                 || l.loc.x > WIDTH as f32 + epsilon
@@ -334,31 +1160,37 @@ impl Game {
                 || l.loc.y > HEIGHT as f32 + epsilon
         }
 
-        let mut i = 0;
-        while i < self.lasers.len() {
+        let margin = self.config.laser_length;
+        self.lasers.retain(|_, laser| {
             // remove lasers outside the screen
-            if laser_out_of_bounds(&self.lasers[i], 30.0) {
-                self.lasers.remove(i);
-            } else {
-                let laser = &mut self.lasers[i];
+            if laser_out_of_bounds(laser, margin) {
+                return false;
+            }
 
-                // update and draw laser if still in screen
-                laser.loc.x += laser.vx * (dt as f32 / 1000.0);
-                laser.loc.y += laser.vy * (dt as f32 / 1000.0);
+            // update laser if still in screen
+            laser.prev_loc = laser.loc;
+            laser.loc.x += laser.vx * TICK_DT;
+            laser.loc.y += laser.vy * TICK_DT;
+            true
+        });
+    }
 
-                self.lines.add_line(
-                    Point {
-                        x: laser.loc.x + laser.vx * -30.0 / 500.0,
-                        y: laser.loc.y + laser.vy * -30.0 / 500.0,
-                    },
-                    Point {
-                        x: laser.loc.x,
-                        y: laser.loc.y,
-                    },
-                );
+    fn draw_lasers(&mut self, alpha: f32) {
+        for laser in self.lasers.values() {
+            let tip = Point {
+                x: laser.prev_loc.x + (laser.loc.x - laser.prev_loc.x) * alpha,
+                y: laser.prev_loc.y + (laser.loc.y - laser.prev_loc.y) * alpha,
+            };
 
-                i += 1;
-            }
+            // trailing back from the tip along the way it's flying
+            let scale = self.config.laser_length / laser.vx.hypot(laser.vy).max(f32::EPSILON);
+            self.lines.add_line(
+                Point {
+                    x: tip.x - laser.vx * scale,
+                    y: tip.y - laser.vy * scale,
+                },
+                tip,
+            );
         }
     }
 
@@ -366,24 +1198,20 @@ impl Game {
         let angle_deg = self.cur_angle.to_degrees();
         let side1_deg = angle_deg - 6.0;
         let side2_deg = angle_deg + 6.0;
+        let base = self.planet_size + self.config.ship_distance;
+        let nose = base + self.config.ship_length;
 
         let pt1 = Point {
-            x: (self.distance + self.planet_size) * side1_deg.to_radians().cos()
-                + (WIDTH / 2) as f32,
-            y: -(self.distance + self.planet_size) * side1_deg.to_radians().sin()
-                + (HEIGHT / 2) as f32,
+            x: base * side1_deg.to_radians().cos() + (WIDTH / 2) as f32,
+            y: -base * side1_deg.to_radians().sin() + (HEIGHT / 2) as f32,
         };
         let pt2 = Point {
-            x: (self.distance + self.planet_size + 30.0) * angle_deg.to_radians().cos()
-                + (WIDTH / 2) as f32,
-            y: -(self.distance + self.planet_size + 30.0) * angle_deg.to_radians().sin()
-                + (HEIGHT / 2) as f32,
+            x: nose * angle_deg.to_radians().cos() + (WIDTH / 2) as f32,
+            y: -nose * angle_deg.to_radians().sin() + (HEIGHT / 2) as f32,
         };
         let pt3 = Point {
-            x: (self.distance + self.planet_size) * side2_deg.to_radians().cos()
-                + (WIDTH / 2) as f32,
-            y: -(self.distance + self.planet_size) * side2_deg.to_radians().sin()
-                + (HEIGHT / 2) as f32,
+            x: base * side2_deg.to_radians().cos() + (WIDTH / 2) as f32,
+            y: -base * side2_deg.to_radians().sin() + (HEIGHT / 2) as f32,
         };
 
         self.lines.add_line(pt1, pt2);
@@ -392,7 +1220,9 @@ impl Game {
     }
 
     fn draw_planet(&mut self) {
-        self.planet_size = 100.0 + 10.0 * (self.current_ms as f64 / 500.0).sin() as f32;
+        // the wobble is derived from the current time rather than drawn from
+        // `rng`, so rendering never changes how the simulation plays out
+        let mut rng = StdRng::seed_from_u64(self.seed ^ self.current_ms as u64);
 
         let mut first_point = Point {
             x: self.planet_size * 0.0f32.to_radians().cos() + (WIDTH / 2) as f32,
@@ -401,11 +1231,11 @@ impl Game {
         let mut last_point = first_point;
         let mut current_point = first_point;
         for theta in (0..360).step_by(6) {
-            let point_deviance = self.planet_size + rand::thread_rng().gen_range(-5.0..5.0);
+            let point_deviance = self.planet_size + rng.gen_range(-5.0..5.0);
 
             current_point = Point {
                 x: point_deviance * (theta as f32).to_radians().cos() + (WIDTH / 2) as f32,
-                y: -1.0 * point_deviance * (theta as f32).to_radians().sin() + (HEIGHT / 2) as f32,
+                y: -point_deviance * (theta as f32).to_radians().sin() + (HEIGHT / 2) as f32,
             };
 
             // save the first point to connect the last point up to it to complete the path
@@ -422,58 +1252,707 @@ impl Game {
     }
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which screen the game is on. Runs go from the title screen to playing,
+/// possibly pausing and resuming, until the planet runs out of lives and it's
+/// game over, then back to the title screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    /// Waiting for a run to start, showing the high scores.
+    Title,
+    Playing,
+    /// Frozen in the middle of a run.
+    Paused,
+    /// The run has ended, and its initials may be being entered.
+    GameOver,
+}
+
+impl GameState {
+    /// Whether the game can go straight from this state to `next`.
+    pub fn can_become(self, next: GameState) -> bool {
+        use GameState::*;
+        matches!(
+            (self, next),
+            (Title, Playing)
+                | (Playing, Paused)
+                | (Paused, Playing)
+                | (Playing, GameOver)
+                | (Playing | Paused | GameOver, Title)
+        )
+    }
+}
+
+/// Something noteworthy that happened during a tick, see [`Game::drain_events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// An asteroid reached the planet.
+    PlanetHit,
+    /// A laser destroyed an asteroid while the game was running.
+    AsteroidDestroyed,
+    /// The planet ran out of lives, ending the run.
+    GameOver,
+}
+
+/// View of the playfield, as returned by [`Game::camera`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Point of the playfield shown at the centre of the viewport.
+    pub center: Point,
+    /// Magnification, 1.0 fits the playfield to the viewport and smaller
+    /// values zoom out.
+    pub zoom: f32,
+}
+
+impl Camera {
+    /// Converts a point of the playfield as it appears on screen back to
+    /// where it is in the playfield.
+    pub fn to_playfield(&self, p: Point) -> Point {
+        Point {
+            x: self.center.x + (p.x - WIDTH as f32 / 2.0) / self.zoom,
+            y: self.center.y + (p.y - HEIGHT as f32 / 2.0) / self.zoom,
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            center: Point {
+                x: WIDTH as f32 / 2.0,
+                y: HEIGHT as f32 / 2.0,
+            },
+            zoom: 1.0,
+        }
+    }
+}
+
+/// Observable state of a [`Game`], as returned by [`Game::snapshot`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSnapshot {
+    /// Number of fixed ticks simulated so far.
+    pub tick: u128,
+    /// Survival time of the current run in milliseconds.
+    pub game_time: u128,
+    /// Number of the wave being played or about to start, counting from 1.
+    pub wave: usize,
+    pub asteroids_destroyed: u32,
+    pub score: u32,
+    pub lives: u32,
+    pub state: GameState,
+    /// Angle of the ship around the planet in radians.
+    pub cursor_angle: f32,
+    pub planet_size: f32,
+    /// Asteroid centres in screen space.
+    pub asteroids: Vec<Point>,
+    /// Laser tips in screen space.
+    pub lasers: Vec<Point>,
+}
+
 #[derive(Debug)]
 struct Laser {
     loc: Point,
+    prev_loc: Point,
     vx: f32,
     vy: f32,
 }
 
+/// Size tiers of asteroids: each tier splits into two of the next one down
+/// when shot. How big, fast and valuable each is comes from
+/// [`GameConfig::asteroids`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    /// The tier this one splits into, if it's not already the smallest.
+    fn smaller(self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Small => None,
+        }
+    }
+}
+
+/// How far the game has got through its waves.
+#[derive(Debug)]
+struct WaveProgress {
+    /// Counting from 0, past the end of the wave list once it repeats.
+    number: usize,
+    wave: Wave,
+    spawned: u32,
+    /// When the next group spawns, in milliseconds into the run. For a new wave
+    /// that's after its intermission.
+    next_spawn: u128,
+    /// Angle the last asteroid of a sweeping wave came from.
+    sweep_angle: f32,
+}
+
+impl WaveProgress {
+    /// Wave `number`, starting after its intermission from `now`.
+    fn new(number: usize, wave: Wave, now: u128) -> Self {
+        Self {
+            number,
+            next_spawn: now + (wave.intermission * 1000.0) as u128,
+            wave,
+            spawned: 0,
+            sweep_angle: 0.0,
+        }
+    }
+
+    /// Whether the wave's banner should be up.
+    fn in_intermission(&self, now: u128) -> bool {
+        self.spawned == 0 && now < self.next_spawn
+    }
+}
+
+/// An asteroid's outline where it is this tick, as laid out for
+/// `Game::check_collision`.
+struct Collider {
+    id: Id<Asteroid>,
+    center: Point,
+    /// Furthest the outline reaches from `center`.
+    radius: f32,
+    /// Range of `Game::collider_points` holding the outline's corners.
+    points: Range<usize>,
+    destroyed: bool,
+}
+
+/// Corners of the smallest box around `points`.
+fn bounds(points: &[Point]) -> (Point, Point) {
+    points.iter().fold(
+        (
+            Point {
+                x: f32::INFINITY,
+                y: f32::INFINITY,
+            },
+            Point {
+                x: f32::NEG_INFINITY,
+                y: f32::NEG_INFINITY,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Point {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Point {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    )
+}
+
 struct Asteroid {
+    size: AsteroidSize,
+    /// Rough radius of the outline, kept from when it was created so a new
+    /// config can't change the size of asteroids already in play.
+    radius: f32,
     loc: Point,
+    prev_loc: Point,
+    /// Rotation of `outline` around `loc`, in radians.
+    angle: f32,
+    prev_angle: f32,
     vx: f32,
     vy: f32,
-    p1: Point,
-    p2: Point,
-    p3: Point,
-    p4: Point,
+    /// Corners of the polygon relative to `loc`, before rotation.
+    outline: Vec<Point>,
+    /// Furthest any corner of `outline` reaches from `loc`.
+    bounding_radius: f32,
     rotation_speed: f32,
 }
 
 impl Asteroid {
-    pub fn update(&mut self, dt: u128) {
-        // update position
-        self.loc.x += self.vx * (dt as f32 / 1000.0);
-        self.loc.y += self.vy * (dt as f32 / 1000.0);
-        self.p1.x += self.vx * (dt as f32 / 1000.0);
-        self.p1.y += self.vy * (dt as f32 / 1000.0);
-        self.p2.x += self.vx * (dt as f32 / 1000.0);
-        self.p2.y += self.vy * (dt as f32 / 1000.0);
-        self.p3.x += self.vx * (dt as f32 / 1000.0);
-        self.p3.y += self.vy * (dt as f32 / 1000.0);
-        self.p4.x += self.vx * (dt as f32 / 1000.0);
-        self.p4.y += self.vy * (dt as f32 / 1000.0);
-
-        // rotate points
-        fn rotate(mut pt: Point, loc: Point, angle: f32) -> Point {
-            pt.x -= loc.x;
-            pt.y -= loc.y;
-
-            let new_x = pt.x * angle.cos() - pt.y * angle.sin();
-            let new_y = pt.y * angle.cos() + pt.x * angle.sin();
-
-            pt.x = new_x;
-            pt.y = new_y;
-
-            pt.x += loc.x;
-            pt.y += loc.y;
-
-            Point { x: pt.x, y: pt.y }
-        }
-
-        self.p1 = rotate(self.p1, self.loc, self.rotation_speed);
-        self.p2 = rotate(self.p2, self.loc, self.rotation_speed);
-        self.p3 = rotate(self.p3, self.loc, self.rotation_speed);
-        self.p4 = rotate(self.p4, self.loc, self.rotation_speed);
+    /// Transforms the outline into the playfield, between where it was on
+    /// the last tick (at `alpha` 0.0) and where it is now (at 1.0).
+    fn points(&self, alpha: f32) -> impl Iterator<Item = Point> + '_ {
+        let loc = Point {
+            x: self.prev_loc.x + (self.loc.x - self.prev_loc.x) * alpha,
+            y: self.prev_loc.y + (self.loc.y - self.prev_loc.y) * alpha,
+        };
+        let angle = self.prev_angle + (self.angle - self.prev_angle) * alpha;
+        let (sin, cos) = angle.sin_cos();
+
+        self.outline.iter().map(move |pt| Point {
+            x: loc.x + pt.x * cos - pt.y * sin,
+            y: loc.y + pt.y * cos + pt.x * sin,
+        })
+    }
+
+    /// Each side of the polygon as a pair of corners, see `points`.
+    fn edges(&self, alpha: f32) -> Vec<(Point, Point)> {
+        let points: Vec<Point> = self.points(alpha).collect();
+        let next = points.iter().cycle().skip(1);
+        points.iter().copied().zip(next.copied()).collect()
+    }
+
+    /// Whether the asteroid has reached a planet `planet_size` across.
+    fn hits_planet(&self, planet_size: f32) -> bool {
+        let center = Point {
+            x: WIDTH as f32 / 2.0,
+            y: HEIGHT as f32 / 2.0,
+        };
+        // most asteroids are nowhere near, which is cheap to rule out
+        let reach = planet_size + self.bounding_radius;
+        if (self.loc.x - center.x).hypot(self.loc.y - center.y) > reach {
+            return false;
+        }
+
+        let polygon: Vec<Point> = self.points(1.0).collect();
+        collision::polygon_intersects_circle(&polygon, center, planet_size)
+    }
+
+    /// Moves and spins the asteroid by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        self.prev_loc = self.loc;
+        self.prev_angle = self.angle;
+        self.loc.x += self.vx * dt;
+        self.loc.y += self.vy * dt;
+        self.angle += self.rotation_speed * dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::ReplayError;
+
+    /// Waves of `asteroids` medium asteroids each, one every half second.
+    fn waves(asteroids: u32) -> Waves {
+        Waves::from_toml(&format!(
+            r#"
+            [[wave]]
+            asteroids = {}
+            mix = {{ medium = 1 }}
+            speed = [20.0, 20.0]
+            spawn_interval = 0.5
+            "#,
+            asteroids
+        ))
+        .unwrap()
+    }
+
+    /// Plays a run to the end, firing every `fire_every` milliseconds while
+    /// sweeping the cursor around the planet.
+    fn play_run(game: &mut Game, fire_every: u32) {
+        let mut ms = 0;
+        while game.state() == GameState::Playing {
+            let angle = ms as f32 / 700.0;
+            game.set_cursor(
+                (500.0 + 300.0 * angle.cos()) as u32,
+                (500.0 + 300.0 * angle.sin()) as u32,
+            );
+            if ms % fire_every == 0 {
+                game.fire();
+            }
+            game.step(10);
+            ms += 10;
+        }
+    }
+
+    #[test]
+    fn headless_game_runs_to_game_over() {
+        let mut game = Game::with_seed(4);
+        game.start();
+        // nobody at the controls, so the asteroids get through
+        for _ in 0..600 {
+            game.step(1000);
+            if game.state() == GameState::GameOver {
+                break;
+            }
+        }
+        let snapshot = game.snapshot();
+        assert_eq!(snapshot.state, GameState::GameOver);
+        assert_eq!(snapshot.lives, 0);
+        assert!(snapshot.game_time > 0);
+        assert!(snapshot.asteroids.is_empty());
+    }
+
+    #[test]
+    fn same_seed_and_inputs_play_out_the_same() {
+        let mut games = [Game::with_seed(5), Game::with_seed(5)];
+        for game in &mut games {
+            game.start();
+            play_run(game, 250);
+        }
+        assert_eq!(games[0].snapshot(), games[1].snapshot());
+
+        let mut other = Game::with_seed(6);
+        other.start();
+        play_run(&mut other, 250);
+        assert_ne!(other.snapshot(), games[0].snapshot());
+    }
+
+    #[test]
+    fn stepping_is_independent_of_frame_length() {
+        let mut whole = Game::with_seed(9);
+        let mut split = Game::with_seed(9);
+        // a crowd of asteroids and a few seconds of firing all round, to have
+        // them colliding with lasers
+        for game in [&mut whole, &mut split] {
+            game.start();
+            for _ in 0..30 {
+                game.add_asteroid();
+            }
+            for ms in (0..3000).step_by(10) {
+                let angle = ms as f32 / 300.0;
+                game.set_cursor(
+                    (500.0 + 300.0 * angle.cos()) as u32,
+                    (500.0 + 300.0 * angle.sin()) as u32,
+                );
+                if ms % 50 == 0 {
+                    game.fire();
+                }
+                game.step(10);
+            }
+        }
+        let before = whole.snapshot();
+        assert!(!before.lasers.is_empty());
+
+        whole.step(1000);
+        for _ in 0..1000 {
+            split.step(1);
+        }
+        assert!(whole.snapshot().asteroids_destroyed > before.asteroids_destroyed);
+        assert_eq!(whole.snapshot(), split.snapshot());
+    }
+
+    /// Plays the current run to the end a tick at a time, with inputs on
+    /// ticks counted from the start of the run.
+    fn play_run_by_ticks(game: &mut Game, fire_every: u32) {
+        let mut tick = 0;
+        while game.state() == GameState::Playing {
+            let angle = tick as f32 / 84.0;
+            game.set_cursor(
+                (500.0 + 300.0 * angle.cos()) as u32,
+                (500.0 + 300.0 * angle.sin()) as u32,
+            );
+            if tick % fire_every == 0 {
+                game.fire();
+            }
+            game.tick();
+            tick += 1;
+        }
+    }
+
+    #[test]
+    fn later_runs_are_reproduced_by_their_seed() {
+        for seed in 0..12 {
+            let mut game = Game::with_seed(seed);
+            game.start();
+            // restarting at ticks landing on every part of a millisecond
+            for _ in 0..(500 + seed * 37) {
+                game.tick();
+            }
+            game.restart();
+            assert_ne!(game.run_seed(), game.seed());
+            let fire_every = 20 + seed as u32 % 5;
+            play_run_by_ticks(&mut game, fire_every);
+            let entry = game.high_scores().entries()[0].clone();
+
+            let mut replayed = Game::with_seed(entry.seed);
+            replayed.start();
+            play_run_by_ticks(&mut replayed, fire_every);
+
+            assert!(game.score > 0, "seed {}", seed);
+            assert_eq!(replayed.score, game.score, "seed {}", seed);
+            assert_eq!(replayed.game_time as u64, entry.time, "seed {}", seed);
+            assert_eq!(
+                replayed.asteroids_destroyed, game.asteroids_destroyed,
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn ranked_runs_are_left_to_the_caller_to_save() {
+        let mut game = Game::with_seed(3);
+        game.start();
+        play_run(&mut game, 200);
+        assert!(game.score > 0);
+        assert_eq!(game.high_scores().entries().len(), 1);
+        assert!(game.take_high_scores_changed());
+        assert!(!game.take_high_scores_changed());
+
+        game.confirm_initials();
+        assert_eq!(game.high_scores().entries()[0].initials.as_deref(), Some("AAA"));
+        assert!(game.take_high_scores_changed());
+    }
+
+    #[test]
+    fn replays_keep_to_the_tuning_they_were_recorded_with() {
+        let mut game = Game::with_waves(2, waves(10));
+        game.start_recording();
+        game.start();
+        play_run(&mut game, 250);
+        let replay = game.take_replay().unwrap();
+
+        assert!(matches!(replay.play(), Err(ReplayError::TuningMismatch)));
+        let played = replay.play_with(GameConfig::default(), waves(10)).unwrap();
+        assert_eq!(played.snapshot(), game.snapshot());
+
+        let mut game = Game::with_seed(2);
+        game.start_recording();
+        game.set_config(GameConfig::default());
+        assert!(game.recording.is_some());
+        game.set_waves(waves(10));
+        assert!(game.recording.is_none());
+    }
+
+    #[test]
+    fn replays_start_with_the_configured_lives() {
+        let config = GameConfig {
+            lives: 2,
+            ..GameConfig::default()
+        };
+        // configured after creation, as the app does
+        let mut game = Game::with_seed(2);
+        game.set_config(config.clone());
+        game.start_recording();
+        game.start();
+        assert_eq!(game.snapshot().lives, 2);
+        play_run(&mut game, 250);
+        let replay = game.take_replay().unwrap();
+
+        let played = replay.play_with(config, Waves::default()).unwrap();
+        assert_eq!(played.snapshot(), game.snapshot());
+    }
+
+    /// A game in `state`, in the middle of a run unless that's `Title`.
+    fn game_in(state: GameState) -> Game {
+        let mut game = Game::with_seed(8);
+        if state == GameState::Title {
+            return game;
+        }
+        game.start();
+        for _ in 0..10 {
+            game.add_asteroid();
+        }
+        game.fire();
+        game.step(500);
+        match state {
+            GameState::Paused => game.pause(),
+            GameState::GameOver => play_run(&mut game, 1000),
+            _ => {}
+        }
+        assert_eq!(game.state(), state);
+        game
+    }
+
+    #[test]
+    fn state_transitions() {
+        use GameState::*;
+        let states = [Title, Playing, Paused, GameOver];
+        let allowed = [
+            (Title, Playing),
+            (Playing, Paused),
+            (Paused, Playing),
+            (Playing, GameOver),
+            (Playing, Title),
+            (Paused, Title),
+            (GameOver, Title),
+        ];
+        for from in states {
+            for to in states {
+                assert_eq!(
+                    from.can_become(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} to {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+
+        // inputs that don't apply leave the state alone
+        let mut game = game_in(Title);
+        game.pause();
+        game.resume();
+        game.fire();
+        assert_eq!(game.state(), Title);
+        assert_eq!(game.snapshot().lasers.len(), 0);
+
+        let mut game = game_in(Playing);
+        let tick = game.current_tick();
+        game.start();
+        game.resume();
+        assert_eq!(game.state(), Playing);
+        assert_eq!(game.current_tick(), tick);
+
+        let mut game = game_in(Paused);
+        game.start();
+        game.fire();
+        assert_eq!(game.state(), Paused);
+
+        let mut game = game_in(GameOver);
+        game.start();
+        game.pause();
+        game.resume();
+        assert_eq!(game.state(), GameOver);
+        game.return_to_title();
+        assert_eq!(game.state(), Title);
+    }
+
+    #[test]
+    fn paused_games_stand_still() {
+        let mut game = game_in(GameState::Paused);
+        let before = game.snapshot();
+        game.step(5000);
+        game.tick();
+        assert_eq!(game.snapshot(), before);
+
+        game.resume();
+        game.step(1000);
+        assert_eq!(game.current_tick(), before.tick + TICK_RATE);
+    }
+
+    #[test]
+    fn leaving_a_run_clears_the_playfield() {
+        for state in [GameState::Playing, GameState::Paused, GameState::GameOver] {
+            let mut game = game_in(state);
+            game.restart();
+            let snapshot = game.snapshot();
+            assert_eq!(snapshot.state, GameState::Playing);
+            assert!(snapshot.asteroids.is_empty() && snapshot.lasers.is_empty());
+            assert_eq!((snapshot.score, snapshot.game_time), (0, 0));
+
+            let mut game = game_in(state);
+            game.return_to_title();
+            let snapshot = game.snapshot();
+            assert_eq!(snapshot.state, GameState::Title);
+            assert!(snapshot.asteroids.is_empty() && snapshot.lasers.is_empty());
+        }
+    }
+
+    #[test]
+    fn pauses_play_back_without_being_recorded() {
+        let mut game = Game::with_seed(8);
+        game.start_recording();
+        game.start();
+        game.set_cursor(700, 500);
+        game.fire();
+        game.step(2000);
+        game.pause();
+        game.set_cursor(300, 500);
+        game.step(3000);
+        game.resume();
+        game.fire();
+        game.step(2000);
+        let replay = game.take_replay().unwrap();
+
+        let played = replay.play().unwrap();
+        assert_eq!(played.snapshot(), game.snapshot());
+    }
+
+    #[test]
+    fn shrinking_the_wave_mid_run() {
+        let mut game = Game::with_waves(1, waves(40));
+        game.start();
+        game.step(9000);
+        assert!(game.progress.spawned > 5);
+
+        game.set_waves(waves(5));
+        assert_eq!(game.progress.spawned, 5);
+        game.step(1000);
+        assert_eq!(game.progress.spawned, 5);
+    }
+
+    /// Shoots a lone `size` asteroid heading down the playfield at `center`
+    /// with a laser whose tip reached its middle this tick, leaving whatever
+    /// it split into.
+    fn shoot(size: AsteroidSize, center: Point) -> Game {
+        let mut game = Game::with_waves(2, waves(1));
+        game.start();
+        let asteroid = game.create_asteroid(size, center, PI / 2.0);
+        game.asteroids.insert(asteroid);
+        game.lasers.insert(Laser {
+            loc: center,
+            prev_loc: Point {
+                x: center.x,
+                y: center.y + 500.0 * TICK_DT,
+            },
+            vx: 0.0,
+            vy: -500.0,
+        });
+        game.check_collision();
+        game
+    }
+
+    #[test]
+    fn asteroids_split_into_the_next_size_down() {
+        let config = GameConfig::default();
+        let split_angle = config.split_angle.to_radians();
+        let center = Point { x: 500.0, y: 200.0 };
+        for (size, smaller) in [
+            (AsteroidSize::Large, Some(AsteroidSize::Medium)),
+            (AsteroidSize::Medium, Some(AsteroidSize::Small)),
+            (AsteroidSize::Small, None),
+        ] {
+            let game = shoot(size, center);
+            assert!(game.lasers.is_empty(), "{:?}", size);
+            assert_eq!(game.asteroids_destroyed, 1, "{:?}", size);
+            assert_eq!(game.score, config.asteroids.get(size).score, "{:?}", size);
+
+            let fragments: Vec<&Asteroid> = game.asteroids.values().collect();
+            let Some(smaller) = smaller else {
+                assert!(fragments.is_empty(), "{:?}", size);
+                continue;
+            };
+            assert_eq!(fragments.len(), 2, "{:?}", size);
+            let tier = config.asteroids.get(smaller);
+            let mut headings = Vec::new();
+            for fragment in fragments {
+                assert_eq!(fragment.size, smaller);
+                assert_eq!(fragment.radius, tier.radius);
+                assert_eq!(fragment.loc, center);
+                // the wave's speed is fixed at 20, scaled by the size's own
+                let speed = fragment.vx.hypot(fragment.vy);
+                assert!((speed - 20.0 * tier.speed).abs() < 1e-3, "{}", speed);
+                headings.push(fragment.vy.atan2(fragment.vx));
+            }
+            headings.sort_by(f32::total_cmp);
+            assert!((headings[0] - (PI / 2.0 - split_angle)).abs() < 1e-3);
+            assert!((headings[1] - (PI / 2.0 + split_angle)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn craggy_outlines_stay_within_their_bounds() {
+        let config = GameConfig::default();
+        for seed in 0..50 {
+            let mut game = Game::with_waves(seed, waves(1));
+            for size in [
+                AsteroidSize::Large,
+                AsteroidSize::Medium,
+                AsteroidSize::Small,
+            ] {
+                let radius = config.asteroids.get(size).radius;
+                let asteroid = game.create_asteroid(size, Point { x: 0.0, y: 0.0 }, 0.0);
+                let corners = asteroid.outline.len();
+                assert!((7..=12).contains(&corners), "seed {}: {}", seed, corners);
+
+                let distances: Vec<f32> = asteroid.outline.iter().map(|p| p.x.hypot(p.y)).collect();
+                for &distance in &distances {
+                    let within = radius * 0.6 - 1e-3..=radius * 1.1 + 1e-3;
+                    assert!(within.contains(&distance), "seed {}: {}", seed, distance);
+                }
+                // collisions and culling go by this, so it has to reach the
+                // furthest corner and no further
+                let furthest = distances.iter().copied().fold(0.0, f32::max);
+                assert_eq!(asteroid.bounding_radius, furthest, "seed {}", seed);
+            }
+        }
     }
 }
@@ -0,0 +1,138 @@
+use crate::draw::Point;
+
+/// Uniform grid over a region of the playfield, bucketing items by the cells
+/// their bounding boxes cover so only the items in the cell a point falls in
+/// need to be tested against it. Boxes and points outside the region are
+/// clamped to its edge cells, so nothing is ever missed, just tested less
+/// efficiently.
+#[derive(Debug)]
+pub struct Grid<T> {
+    origin: Point,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<T>>,
+    // indices of the cells holding anything, so clearing skips empty ones
+    occupied: Vec<usize>,
+}
+
+impl<T: Copy> Grid<T> {
+    /// Covers the box from `min` to `max` with square cells `cell_size` across.
+    pub fn new(min: Point, max: Point, cell_size: f32) -> Self {
+        let columns = ((max.x - min.x) / cell_size).ceil().max(1.0) as usize;
+        let rows = ((max.y - min.y) / cell_size).ceil().max(1.0) as usize;
+        Self {
+            origin: min,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            occupied: Vec::new(),
+        }
+    }
+
+    /// Empties every cell, keeping their allocations for the next tick.
+    pub fn clear(&mut self) {
+        for &index in &self.occupied {
+            self.cells[index].clear();
+        }
+        self.occupied.clear();
+    }
+
+    /// Column and row ranges of the cells the box from `min` to `max` covers.
+    fn cell_range(&self, min: Point, max: Point) -> (usize, usize, usize, usize) {
+        let column = |x: f32| {
+            (((x - self.origin.x) / self.cell_size).max(0.0) as usize).min(self.columns - 1)
+        };
+        let row =
+            |y: f32| (((y - self.origin.y) / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (column(min.x), column(max.x), row(min.y), row(max.y))
+    }
+
+    /// Adds `item` to every cell the box from `min` to `max` covers.
+    pub fn insert(&mut self, item: T, min: Point, max: Point) {
+        let (x0, x1, y0, y1) = self.cell_range(min, max);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let index = y * self.columns + x;
+                if self.cells[index].is_empty() {
+                    self.occupied.push(index);
+                }
+                self.cells[index].push(item);
+            }
+        }
+    }
+
+    /// Every item whose box covers `p`, along with some whose boxes only
+    /// cover other parts of its cell, in the order they were inserted.
+    pub fn at(&self, p: Point) -> &[T] {
+        let (x, _, y, _) = self.cell_range(p, p);
+        &self.cells[y * self.columns + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    /// 4 x 3 cells of 10 x 10.
+    fn grid() -> Grid<u32> {
+        Grid::new(pt(0.0, 0.0), pt(40.0, 30.0), 10.0)
+    }
+
+    #[test]
+    fn points_past_the_edges_fall_in_edge_cells() {
+        let mut grid = grid();
+        grid.insert(1, pt(1.0, 1.0), pt(2.0, 2.0));
+        grid.insert(2, pt(38.0, 28.0), pt(39.0, 29.0));
+        assert_eq!(grid.at(pt(-50.0, -50.0)), [1]);
+        assert_eq!(grid.at(pt(5.0, -1.0)), [1]);
+        assert_eq!(grid.at(pt(100.0, 100.0)), [2]);
+        assert_eq!(grid.at(pt(40.0, 30.0)), [2]);
+    }
+
+    #[test]
+    fn boxes_past_the_edges_fill_edge_cells() {
+        let mut grid = grid();
+        grid.insert(1, pt(-100.0, -100.0), pt(-50.0, 5.0));
+        grid.insert(2, pt(35.0, 25.0), pt(500.0, 500.0));
+        assert_eq!(grid.at(pt(0.0, 0.0)), [1]);
+        assert!(grid.at(pt(0.0, 15.0)).is_empty());
+        assert!(grid.at(pt(15.0, 0.0)).is_empty());
+        assert_eq!(grid.at(pt(39.0, 29.0)), [2]);
+        assert!(grid.at(pt(25.0, 29.0)).is_empty());
+    }
+
+    #[test]
+    fn boxes_spanning_cells_are_found_from_each() {
+        let mut grid = grid();
+        grid.insert(1, pt(5.0, 5.0), pt(25.0, 15.0));
+        grid.insert(2, pt(15.0, 12.0), pt(16.0, 13.0));
+        for x in [5.0, 15.0, 25.0] {
+            for y in [5.0, 15.0] {
+                assert!(grid.at(pt(x, y)).contains(&1), "({}, {})", x, y);
+            }
+        }
+        assert_eq!(grid.at(pt(11.0, 19.0)), [1, 2]);
+        assert!(grid.at(pt(35.0, 5.0)).is_empty());
+        assert!(grid.at(pt(5.0, 25.0)).is_empty());
+    }
+
+    #[test]
+    fn clearing_empties_every_cell() {
+        let mut grid = grid();
+        grid.insert(1, pt(0.0, 0.0), pt(40.0, 30.0));
+        grid.clear();
+        for x in [5.0, 15.0, 25.0, 35.0] {
+            for y in [5.0, 15.0, 25.0] {
+                assert!(grid.at(pt(x, y)).is_empty());
+            }
+        }
+        grid.insert(2, pt(12.0, 12.0), pt(13.0, 13.0));
+        assert_eq!(grid.at(pt(15.0, 15.0)), [2]);
+    }
+}
@@ -1,11 +1,32 @@
-mod draw;
-mod game;
+mod bloom;
+mod collision;
+pub mod config;
+mod crt;
+pub mod draw;
+pub mod entity;
+pub mod feedback;
+mod font;
+mod fullscreen;
+pub mod game;
+mod grid;
 mod icon;
+mod particles;
+#[cfg(not(target_arch = "wasm32"))]
+mod reload;
+pub mod replay;
+pub mod scores;
+pub mod waves;
 
 #[cfg(target_arch="wasm32")]
 use wasm_bindgen::prelude::*;
 
-use game::Game;
+use bloom::Bloom;
+use crt::{Crt, CrtSettings};
+use draw::Viewport;
+use feedback::FeedbackSettings;
+use game::{Camera, Game, GameState};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use wgpu::{include_wgsl, util::DeviceExt};
 use winit::{
     event::*,
@@ -20,10 +41,20 @@ pub struct Vertex {
     color: [f32; 3],
 }
 
-impl Vertex {
+/// Vertex of a line segment expanded into a quad, see `draw::expand_lines`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct QuadVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+    // signed distance from the centre of the line and its half width, in pixels
+    edge: [f32; 2],
+}
+
+impl QuadVertex {
     fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            array_stride: std::mem::size_of::<QuadVertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
             attributes: &[
                 wgpu::VertexAttribute {
@@ -36,15 +67,35 @@ impl Vertex {
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
 }
 
-const VERTICES: &[Vertex] = &[Vertex {
-    position: [0.0, 0.0, 0.0],
-    color: [1.0, 1.0, 1.0],
-}; 1024];
+/// Width of the vector lines in logical pixels.
+pub const LINE_WIDTH: f32 = 2.0;
+
+/// Number of vertices the vertex buffer starts out with room for; it grows
+/// whenever a frame needs more.
+const INITIAL_VERTEX_CAPACITY: usize = 4096;
+
+/// Milliseconds after a run ends during which clicks are ignored, so the
+/// player still firing when the planet fell doesn't pick their initials.
+const INITIALS_CLICK_GRACE_MS: u128 = 1000;
+
+fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Vertex Buffer"),
+        size: (capacity * std::mem::size_of::<QuadVertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
 
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
@@ -54,25 +105,6 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
     0.0, 0.0, 0.5, 1.0,
 );
 
-struct Camera {
-    eye: cgmath::Point3<f32>,
-    target: cgmath::Point3<f32>,
-    up: cgmath::Vector3<f32>,
-    aspect: f32,
-    fovy: f32,
-    znear: f32,
-    zfar: f32,
-}
-
-impl Camera {
-    fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
-        let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
-        let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
-
-        return OPENGL_TO_WGPU_MATRIX * proj * view;
-    }
-}
-
 // We need this for Rust to store our data correctly for the shaders
 #[repr(C)]
 // This is so we can store this in a buffer
@@ -91,8 +123,26 @@ impl CameraUniform {
         }
     }
 
-    fn update_view_proj(&mut self, camera: &Camera) {
-        self.view_proj = camera.build_view_projection_matrix().into();
+    /// Looks at the playfield through `camera`, for quads laid out by
+    /// `draw::expand_lines` on the surface described by `viewport`.
+    fn update_view_proj(&mut self, camera: &Camera, viewport: &Viewport) {
+        use cgmath::{Matrix4, Vector3};
+
+        // the quads are in pixels from the centre of the playfield with y up,
+        // while the camera works in playfield units with y down
+        let offset = Vector3::new(
+            (WIDTH as f32 / 2.0 - camera.center.x) * viewport.scale,
+            (camera.center.y - HEIGHT as f32 / 2.0) * viewport.scale,
+            0.0,
+        );
+        let view = Matrix4::from_scale(camera.zoom) * Matrix4::from_translation(offset);
+        let (half_width, half_height) = (
+            viewport.surface_width / 2.0,
+            viewport.surface_height / 2.0,
+        );
+        let proj = cgmath::ortho(-half_width, half_width, -half_height, half_height, -1.0, 1.0);
+
+        self.view_proj = (OPENGL_TO_WGPU_MATRIX * proj * view).into();
     }
 }
 
@@ -102,14 +152,33 @@ struct State {
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
+    viewport: Viewport,
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
+    vertex_capacity: usize,
     num_vertices: u32,
-    _camera: Camera,
-    _camera_uniform: CameraUniform,
-    _camera_buffer: wgpu::Buffer,
+    // the playfield's quads come first in the vertex buffer, followed by the HUD's
+    num_playfield_vertices: u32,
+    // scratch space the game's lines are expanded into every frame
+    quad_vertices: Vec<QuadVertex>,
+    /// Width of the lines in physical pixels.
+    line_width: f32,
+    camera_uniform: CameraUniform,
+    camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    // the HUD is drawn through a camera that never moves
+    hud_camera_uniform: CameraUniform,
+    hud_camera_buffer: wgpu::Buffer,
+    hud_camera_bind_group: wgpu::BindGroup,
+    bloom: Bloom,
+    bloom_enabled: bool,
+    crt: Crt,
     game: Game,
+    // mouse wheel movement not yet turned into whole steps, in lines
+    wheel_lines: f32,
+    // reloads the config and waves when they're edited
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<reload::FileWatcher>,
 }
 
 impl State {
@@ -159,28 +228,21 @@ impl State {
 
         let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
 
-        let camera = Camera {
-            // position the camera one unit up and 2 units back
-            // +z is out of the screen
-            eye: (0.0, 1.0, 2.0).into(),
-            // have it look at the origin
-            target: (0.0, 0.0, 0.0).into(),
-            // which way is "up"
-            up: cgmath::Vector3::unit_y(),
-            aspect: config.width as f32 / config.height as f32,
-            fovy: 45.0,
-            znear: 0.1,
-            zfar: 100.0,
-        };
+        let viewport = Viewport::new(size.width as f32, size.height as f32);
 
         let mut camera_uniform = CameraUniform::new();
-        camera_uniform.update_view_proj(&camera);
-
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera_uniform]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
+        camera_uniform.update_view_proj(&Camera::default(), &viewport);
+        let hud_camera_uniform = camera_uniform;
+
+        let create_camera_buffer = |label, uniform: &CameraUniform| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&[*uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        };
+        let camera_buffer = create_camera_buffer("Camera Buffer", &camera_uniform);
+        let hud_camera_buffer = create_camera_buffer("HUD Camera Buffer", &hud_camera_uniform);
 
         let camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -197,14 +259,19 @@ impl State {
                 label: Some("camera_bind_group_layout"),
             });
 
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
-        });
+        let create_camera_bind_group = |label, buffer: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some(label),
+            })
+        };
+        let camera_bind_group = create_camera_bind_group("camera_bind_group", &camera_buffer);
+        let hud_camera_bind_group =
+            create_camera_bind_group("hud_camera_bind_group", &hud_camera_buffer);
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -219,24 +286,24 @@ impl State {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
+                buffers: &[QuadVertex::desc()],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
             primitive: wgpu::PrimitiveState {
-                // topology: wgpu::PrimitiveTopology::TriangleList,
-                topology: wgpu::PrimitiveTopology::LineList,
-                // topology: wgpu::PrimitiveTopology::LineStrip,
+                // lines are expanded into quads on the CPU, see `draw::expand_lines`
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // the winding of a quad depends on the direction of its line
+                cull_mode: None,
                 // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
                 polygon_mode: wgpu::PolygonMode::Fill,
                 // Requires Features::DEPTH_CLIP_CONTROL
@@ -253,15 +320,29 @@ impl State {
             multiview: None,
         });
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(VERTICES),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-        });
+        let vertex_buffer = create_vertex_buffer(&device, INITIAL_VERTEX_CAPACITY);
 
-        let num_vertices = VERTICES.len() as u32;
+        let bloom = Bloom::new(&device, &queue, &config);
+        let crt = Crt::new(&device, &config, CrtSettings::default());
 
-        let game = Game::new();
+        let mut game = Game::new();
+        match scores::HighScores::load() {
+            Ok(high_scores) => game.set_high_scores(high_scores),
+            Err(e) => log::error!("Couldn't load high scores: {}", e),
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let watcher = {
+            load_config(&mut game);
+            load_waves(&mut game);
+            if std::env::var_os(RECORD_REPLAY_VAR).is_some() {
+                game.start_recording();
+            }
+            reload::FileWatcher::new(ASSETS_DIR)
+                .map_err(|e| {
+                    log::warn!("Changes to {} won't apply until restart: {}", ASSETS_DIR, e)
+                })
+                .ok()
+        };
 
         Self {
             surface,
@@ -269,38 +350,156 @@ impl State {
             queue,
             config,
             size,
+            viewport,
             render_pipeline,
             vertex_buffer,
-            num_vertices,
-            _camera: camera,
-            _camera_uniform: camera_uniform,
-            _camera_buffer: camera_buffer,
+            vertex_capacity: INITIAL_VERTEX_CAPACITY,
+            num_vertices: 0,
+            num_playfield_vertices: 0,
+            quad_vertices: Vec::new(),
+            line_width: LINE_WIDTH * window.scale_factor() as f32,
+            camera_uniform,
+            camera_buffer,
             camera_bind_group,
+            hud_camera_uniform,
+            hud_camera_buffer,
+            hud_camera_bind_group,
+            bloom,
+            bloom_enabled: true,
+            crt,
             game,
+            wheel_lines: 0.0,
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher,
+        }
+    }
+
+    /// Reloads the config or waves if they've changed on disk since the last
+    /// frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_changed_files(&mut self) {
+        let changed = match &self.watcher {
+            Some(watcher) => watcher.changed(),
+            None => return,
+        };
+        let is_changed = |path: &str| {
+            changed
+                .iter()
+                .any(|name| Some(name.as_os_str()) == Path::new(path).file_name())
+        };
+
+        if is_changed(config::CONFIG_PATH) {
+            load_config(&mut self.game);
+        }
+        if is_changed(waves::WAVES_PATH) {
+            load_waves(&mut self.game);
+        }
+    }
+
+    /// Writes the recorded replay, if any, to the path in `RECORD_REPLAY_VAR`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_replay(&mut self) {
+        if let (Some(replay), Some(path)) = (
+            self.game.take_replay(),
+            std::env::var_os(RECORD_REPLAY_VAR),
+        ) {
+            if let Err(e) = replay.save(&path) {
+                log::error!("Couldn't save replay to {:?}: {}", path, e);
+            }
         }
     }
 
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.line_width = LINE_WIDTH * scale_factor as f32;
+    }
+
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
+            self.viewport = Viewport::new(new_size.width as f32, new_size.height as f32);
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.bloom
+                .resize(&self.device, &self.queue, new_size.width, new_size.height);
+            self.crt.resize(&self.device, new_size.width, new_size.height);
+
+            self.hud_camera_uniform
+                .update_view_proj(&Camera::default(), &self.viewport);
+            self.queue.write_buffer(
+                &self.hud_camera_buffer,
+                0,
+                bytemuck::cast_slice(&[self.hud_camera_uniform]),
+            );
         }
     }
 
     fn input(&mut self, event: &WindowEvent) -> bool {
+        if self.game.initials().is_some() && self.input_initials(event) {
+            return true;
+        }
+
         match event {
             WindowEvent::CursorMoved { position, .. } => {
-                self.game.set_cursor(position.x as u32, position.y as u32);
+                // the cursor is clamped to the edge of the playfield over the
+                // letterbox bars and mapped through the camera, so the ship
+                // aims where the cursor appears to be
+                let p = self.viewport.to_playfield(position.x as f32, position.y as f32);
+                let p = self.game.camera().to_playfield(p);
+                self.game.set_cursor(
+                    p.x.clamp(0.0, WIDTH as f32) as u32,
+                    p.y.clamp(0.0, HEIGHT as f32) as u32,
+                );
                 true
             }
             WindowEvent::MouseInput { button, state, .. } => {
                 if let (MouseButton::Left, ElementState::Pressed) = (button, state) {
-                    self.game.fire();
+                    match self.game.state() {
+                        GameState::Title => self.game.start(),
+                        GameState::Paused => self.game.resume(),
+                        GameState::Playing | GameState::GameOver => self.game.fire(),
+                    }
                 }
                 true
             }
+            // nobody's playing while the window is in the background
+            WindowEvent::Focused(false) => {
+                self.game.pause();
+                true
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } => {
+                match self.game.state() {
+                    GameState::Title => self.game.start(),
+                    GameState::Playing => self.game.pause(),
+                    GameState::Paused => self.game.resume(),
+                    GameState::GameOver => self.game.return_to_title(),
+                }
+                true
+            },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::P),
+                        ..
+                    },
+                ..
+            } => {
+                match self.game.state() {
+                    GameState::Playing => self.game.pause(),
+                    GameState::Paused => self.game.resume(),
+                    GameState::Title | GameState::GameOver => {}
+                }
+                true
+            },
             WindowEvent::KeyboardInput {
                 input:
                     KeyboardInput {
@@ -313,19 +512,160 @@ impl State {
                 self.game.restart();
                 true
             },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::B),
+                        ..
+                    },
+                ..
+            } => {
+                self.bloom_enabled = !self.bloom_enabled;
+                true
+            },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::C),
+                        ..
+                    },
+                ..
+            } => {
+                self.crt.settings.enabled = !self.crt.settings.enabled;
+                if self.crt.settings.enabled {
+                    self.crt.clear_history();
+                }
+                true
+            },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::M),
+                        ..
+                    },
+                ..
+            } => {
+                let settings = &mut self.game.feedback.settings;
+                *settings = if *settings == FeedbackSettings::default() {
+                    FeedbackSettings::reduced_motion()
+                } else {
+                    FeedbackSettings::default()
+                };
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Handles input while initials are being entered for a new high score,
+    /// when the keyboard spells out letters rather than triggering the usual
+    /// keys. Returns false for anything left to `input`, such as aiming.
+    fn input_initials(&mut self, event: &WindowEvent) -> bool {
+        let clicks_ignored = self.game.time_in_state() < INITIALS_CLICK_GRACE_MS;
+        let initials = match self.game.initials_mut() {
+            Some(initials) => initials,
+            None => return false,
+        };
+
+        match event {
+            WindowEvent::ReceivedCharacter(c) => {
+                initials.type_letter(*c);
+                true
+            }
+            WindowEvent::KeyboardInput { input, .. } => {
+                if input.state != ElementState::Pressed {
+                    return false;
+                }
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Up) => initials.cycle(1),
+                    Some(VirtualKeyCode::Down) => initials.cycle(-1),
+                    Some(VirtualKeyCode::Left) | Some(VirtualKeyCode::Back) => {
+                        initials.move_cursor(-1)
+                    }
+                    Some(VirtualKeyCode::Right) => initials.move_cursor(1),
+                    Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) => {
+                        self.game.confirm_initials()
+                    }
+                    // still quits
+                    Some(VirtualKeyCode::Escape) => return false,
+                    // letters arrive as characters instead
+                    _ => {}
+                }
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.wheel_lines += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+                };
+                let steps = self.wheel_lines.trunc();
+                self.wheel_lines -= steps;
+                initials.cycle(steps as i32);
+                true
+            }
+            WindowEvent::MouseInput { button, state, .. } => {
+                // clicking moves on to the next letter, but only Enter confirms
+                // them, so stray clicks can't sign the run
+                if let (MouseButton::Left, ElementState::Pressed) = (button, state) {
+                    if !clicks_ignored {
+                        initials.move_cursor(1);
+                    }
+                }
+                true
+            }
             _ => false,
         }
     }
 
     fn update(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.reload_changed_files();
         self.game.draw();
-        self.queue
-            .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&VERTICES));
+        if self.game.take_high_scores_changed() {
+            if let Err(e) = self.game.high_scores().save() {
+                log::error!("Couldn't save high scores: {}", e);
+            }
+        }
+
+        let camera = self.game.camera();
+        self.camera_uniform.update_view_proj(&camera, &self.viewport);
         self.queue.write_buffer(
-            &self.vertex_buffer,
+            &self.camera_buffer,
             0,
-            bytemuck::cast_slice(&self.game.lines.vertices),
+            bytemuck::cast_slice(&[self.camera_uniform]),
+        );
+
+        self.quad_vertices.clear();
+        draw::expand_lines(
+            &self.game.lines.vertices,
+            self.line_width,
+            camera.zoom,
+            &self.viewport,
+            &mut self.quad_vertices,
         );
+        self.num_playfield_vertices = self.quad_vertices.len() as u32;
+        draw::expand_lines(
+            &self.game.hud.vertices,
+            self.line_width,
+            1.0,
+            &self.viewport,
+            &mut self.quad_vertices,
+        );
+
+        let vertices = &self.quad_vertices;
+        if vertices.len() > self.vertex_capacity {
+            // grow geometrically so a busy scene doesn't reallocate every frame
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = create_vertex_buffer(&self.device, self.vertex_capacity);
+        }
+        if !vertices.is_empty() {
+            self.queue
+                .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+        }
+        self.num_vertices = vertices.len() as u32;
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -338,13 +678,18 @@ impl State {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+
+        // each enabled post-process renders into the input of the next one,
+        // going lines -> bloom -> CRT -> surface
+        let crt_view = self.crt.input_view();
+        let bloom_view = self.bloom.scene_view().filter(|_| self.bloom_enabled);
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[
                     // This is what @location(0) in the fragment shader targets
                     Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
+                        view: bloom_view.or(crt_view).unwrap_or(&view),
                         resolve_target: None,
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -360,10 +705,28 @@ impl State {
                 depth_stencil_attachment: None,
             });
 
+            // keep anything past the edge of the playfield out of the bars
+            let viewport = &self.viewport;
+            let (x, y) = (viewport.x.max(0.0) as u32, viewport.y.max(0.0) as u32);
+            render_pass.set_scissor_rect(
+                x,
+                y,
+                (viewport.width() as u32).clamp(1, self.size.width - x),
+                (viewport.height() as u32).clamp(1, self.size.height - y),
+            );
             render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
             render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..self.num_vertices, 0..1);
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.draw(0..self.num_playfield_vertices, 0..1);
+            render_pass.set_bind_group(0, &self.hud_camera_bind_group, &[]);
+            render_pass.draw(self.num_playfield_vertices..self.num_vertices, 0..1);
+        }
+
+        if bloom_view.is_some() {
+            self.bloom.render(&mut encoder, crt_view.unwrap_or(&view));
+        }
+        if crt_view.is_some() {
+            self.crt.render(&self.queue, &mut encoder, &view);
         }
 
         // submit will accept anything that implements IntoIter
@@ -374,6 +737,46 @@ impl State {
     }
 }
 
+/// When set on native, every input is recorded and the replay is written to
+/// the path it names when the game is closed.
+#[cfg(not(target_arch = "wasm32"))]
+pub const RECORD_REPLAY_VAR: &str = "PLANET_DEFENDER_RECORD";
+
+/// Directory on native holding the files the game is tuned with, which are
+/// watched for changes while it runs.
+#[cfg(not(target_arch = "wasm32"))]
+const ASSETS_DIR: &str = "assets";
+
+/// Loads the config at `config::CONFIG_PATH` into `game`, keeping the one it
+/// has if that fails.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_config(game: &mut Game) {
+    match config::GameConfig::load(config::CONFIG_PATH) {
+        Ok(config) => game.set_config(config),
+        Err(e) => log::error!(
+            "Couldn't load {}, keeping the current config: {}",
+            config::CONFIG_PATH,
+            e
+        ),
+    }
+}
+
+/// Loads the waves at `waves::WAVES_PATH` into `game`, keeping the ones it
+/// has if that fails.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_waves(game: &mut Game) {
+    match waves::Waves::load(waves::WAVES_PATH) {
+        Ok(waves) => game.set_waves(waves),
+        Err(e) => log::error!(
+            "Couldn't load {}, keeping the current waves: {}",
+            waves::WAVES_PATH,
+            e
+        ),
+    }
+}
+
+/// Size of the logical playfield all game coordinates are in, whatever the
+/// size of the window; see `draw::Viewport`.
 pub const WIDTH: u32 = 1000;
 pub const HEIGHT: u32 = 1000;
 
@@ -391,32 +794,28 @@ pub async fn run() {
     let window = WindowBuilder::new()
         .with_title("Planet Defender")
         .with_inner_size(winit::dpi::PhysicalSize {
-            width: 1000,
-            height: 1000,
+            width: WIDTH,
+            height: HEIGHT,
         })
-        .with_resizable(false)
         .with_window_icon(Some(icon::ship_icon()))
         .build(&event_loop)
         .unwrap();
 
     #[cfg(target_arch = "wasm32")]
-    {
-        // Winit prevents sizing with CSS, so we have to set
-        // the size manually when on web.
-        use winit::dpi::PhysicalSize;
-        window.set_inner_size(PhysicalSize::new(1000, 1000));
-        
+    let container = {
         use winit::platform::web::WindowExtWebSys;
-        web_sys::window()
+        let container = web_sys::window()
             .and_then(|win| win.document())
             .and_then(|doc| {
                 let dst = doc.get_element_by_id("planet-defender")?;
                 let canvas = web_sys::Element::from(window.canvas());
                 dst.append_child(&canvas).ok()?;
-                Some(())
+                Some(dst)
             })
             .expect("Couldn't append canvas to document body.");
-    }
+        fit_to_container(&window, &container);
+        container
+    };
 
     let mut state = State::new(&window).await;
 
@@ -424,31 +823,35 @@ pub async fn run() {
         Event::WindowEvent {
             ref event,
             window_id,
-        } if window_id == window.id() => {
-            if !state.input(event) {
-                match event {
-                    // close when user presses close or hits escape
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
+        } if window_id == window.id() && !state.input(event) => match event {
+            // close when user presses close or hits escape
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Escape),
                         ..
-                    } => *control_flow = ControlFlow::Exit,
-                    WindowEvent::Resized(physical_size) => {
-                        state.resize(*physical_size);
-                    }
-                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        // new_inner_size is &&mut so we have to dereference it twice
-                        state.resize(**new_inner_size);
-                    }
-                    _ => {}
-                }
+                    },
+                ..
+            } => {
+                #[cfg(not(target_arch = "wasm32"))]
+                state.save_replay();
+                *control_flow = ControlFlow::Exit
             }
-        }
+            WindowEvent::Resized(physical_size) => {
+                state.resize(*physical_size);
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => {
+                state.set_scale_factor(*scale_factor);
+                // new_inner_size is &&mut so we have to dereference it twice
+                state.resize(**new_inner_size);
+            }
+            _ => {}
+        },
         Event::RedrawRequested(window_id) if window_id == window.id() => {
             state.update();
             match state.render() {
@@ -462,6 +865,9 @@ pub async fn run() {
             }
         }
         Event::MainEventsCleared => {
+            // the canvas doesn't get resize events of its own on web
+            #[cfg(target_arch = "wasm32")]
+            fit_to_container(&window, &container);
             // RedrawRequested will only trigger once, unless we manually
             // request it.
             window.request_redraw();
@@ -469,3 +875,100 @@ pub async fn run() {
         _ => {}
     });
 }
+
+/// Winit prevents sizing with CSS, so on web the canvas is sized to fill the
+/// element it's in by hand whenever that element changes size.
+#[cfg(target_arch = "wasm32")]
+fn fit_to_container(window: &Window, container: &web_sys::Element) {
+    use winit::dpi::LogicalSize;
+    let size = LogicalSize::new(container.client_width(), container.client_height())
+        .to_physical::<u32>(window.scale_factor());
+    if size.width > 0 && size.height > 0 && size != window.inner_size() {
+        window.set_inner_size(size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{Matrix4, Vector4};
+    use draw::{LineHandler, Point};
+
+    /// Corners of the quad a short line drawn at `p` is expanded into, on the
+    /// surface through the same view-projection as a frame.
+    fn on_surface(p: Point, camera: &Camera, viewport: &Viewport) -> Vec<(f32, f32)> {
+        let mut lines = LineHandler::new();
+        lines.add_line(Point { x: p.x - 5.0, ..p }, Point { x: p.x + 5.0, ..p });
+        let mut quads = Vec::new();
+        draw::expand_lines(&lines.vertices, LINE_WIDTH, camera.zoom, viewport, &mut quads);
+
+        let mut uniform = CameraUniform::new();
+        uniform.update_view_proj(camera, viewport);
+        let view_proj = Matrix4::from(uniform.view_proj);
+        [quads[0], quads[1], quads[2], quads[5]]
+            .iter()
+            .map(|corner| {
+                let [x, y, _] = corner.position;
+                let clip = view_proj * Vector4::new(x, y, 0.0, 1.0);
+                (
+                    (clip.x / clip.w + 1.0) / 2.0 * viewport.surface_width,
+                    (1.0 - clip.y / clip.w) / 2.0 * viewport.surface_height,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cursor_maps_back_to_what_is_drawn_under_it() {
+        // letterboxed, zoomed out and looking off centre as when panned
+        // towards the cursor with the screen shaking
+        let viewport = Viewport::new(1600.0, 700.0);
+        for camera in [
+            Camera::default(),
+            Camera {
+                center: Point { x: 530.0, y: 372.0 },
+                zoom: 0.8,
+            },
+            Camera {
+                center: Point { x: 455.0, y: 410.0 },
+                zoom: 1.3,
+            },
+        ] {
+            for p in [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 500.0, y: 400.0 },
+                Point { x: 120.0, y: 650.0 },
+                Point { x: 930.0, y: 75.0 },
+            ] {
+                // the quad's corners surround the line evenly
+                let corners = on_surface(p, &camera, &viewport);
+                let x = corners.iter().map(|c| c.0).sum::<f32>() / 4.0;
+                let y = corners.iter().map(|c| c.1).sum::<f32>() / 4.0;
+                let back = camera.to_playfield(viewport.to_playfield(x, y));
+                assert!(
+                    (back.x - p.x).abs() < 1e-2 && (back.y - p.y).abs() < 1e-2,
+                    "{:?} came back as {:?} through {:?}",
+                    p,
+                    back,
+                    camera
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lines_are_as_wide_at_any_zoom() {
+        let viewport = Viewport::new(1600.0, 700.0);
+        for zoom in [0.5, 0.8, 1.0, 1.3, 2.0] {
+            let camera = Camera {
+                zoom,
+                ..Camera::default()
+            };
+            // the first two corners are across the line from each other,
+            // one pixel past it on either side
+            let corners = on_surface(Point { x: 500.0, y: 400.0 }, &camera, &viewport);
+            let across = (corners[0].1 - corners[1].1).abs();
+            assert!((across - (LINE_WIDTH + 2.0)).abs() < 1e-3, "{}: {}", zoom, across);
+        }
+    }
+}
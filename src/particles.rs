@@ -0,0 +1,244 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f32::consts::PI;
+
+use crate::draw::{Color, LineHandler, Point};
+
+/// Short lived line segment flying away from an explosion or impact.
+#[derive(Debug)]
+struct Particle {
+    loc: Point,
+    prev_loc: Point,
+    vx: f32,
+    vy: f32,
+    /// Half of the segment relative to `loc`, the other half mirrors it.
+    half: Point,
+    /// Radians per second.
+    spin: f32,
+    /// Seconds left to live, the particle fades out as this runs down.
+    life: f32,
+    lifetime: f32,
+    color: Color,
+}
+
+/// Line based particles: shards of destroyed asteroids and sparks from
+/// impacts on the planet. They're purely visual and use their own random
+/// numbers, so spawning them never changes how the simulation plays out.
+#[derive(Debug)]
+pub struct Particles {
+    particles: Vec<Particle>,
+    rng: StdRng,
+}
+
+impl Particles {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            particles: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Breaks each edge of an outline centred on `center` into two shards
+    /// that carry on with the outline's velocity while flying apart.
+    pub fn debris(
+        &mut self,
+        center: Point,
+        edges: &[(Point, Point)],
+        vx: f32,
+        vy: f32,
+        color: Color,
+    ) {
+        for &(a, b) in edges {
+            let mid = Point {
+                x: (a.x + b.x) / 2.0,
+                y: (a.y + b.y) / 2.0,
+            };
+            for (from, to) in [(a, mid), (mid, b)] {
+                let loc = Point {
+                    x: (from.x + to.x) / 2.0,
+                    y: (from.y + to.y) / 2.0,
+                };
+                // outwards from the centre of the outline
+                let (dx, dy) = (loc.x - center.x, loc.y - center.y);
+                let len = dx.hypot(dy).max(f32::EPSILON);
+                let speed = self.rng.gen_range(40.0..120.0);
+                let lifetime = self.rng.gen_range(0.6..1.2);
+
+                self.particles.push(Particle {
+                    loc,
+                    prev_loc: loc,
+                    vx: vx * 0.5 + dx / len * speed,
+                    vy: vy * 0.5 + dy / len * speed,
+                    half: Point {
+                        x: (to.x - from.x) / 2.0,
+                        y: (to.y - from.y) / 2.0,
+                    },
+                    spin: self.rng.gen_range(-6.0..6.0),
+                    life: lifetime,
+                    lifetime,
+                    color,
+                });
+            }
+        }
+    }
+
+    /// Sprays sparks from `at` in a fan around the direction `angle`, in
+    /// radians measured the same way as `atan2` on playfield coordinates.
+    pub fn sparks(&mut self, at: Point, angle: f32, count: usize, color: Color) {
+        for _ in 0..count {
+            let angle = angle + self.rng.gen_range(-PI / 3.0..PI / 3.0);
+            let speed = self.rng.gen_range(150.0..300.0);
+            let length = self.rng.gen_range(4.0..8.0);
+            let lifetime = self.rng.gen_range(0.3..0.6);
+            let (sin, cos) = angle.sin_cos();
+
+            self.particles.push(Particle {
+                loc: at,
+                prev_loc: at,
+                vx: cos * speed,
+                vy: sin * speed,
+                // lined up with the way it's flying
+                half: Point {
+                    x: cos * length,
+                    y: sin * length,
+                },
+                spin: 0.0,
+                life: lifetime,
+                lifetime,
+                color,
+            });
+        }
+    }
+
+    /// Moves, spins and ages every particle by `dt` seconds, dropping the
+    /// ones that have faded out.
+    pub fn update(&mut self, dt: f32) {
+        self.particles.retain_mut(|p| {
+            p.life -= dt;
+            p.prev_loc = p.loc;
+            p.loc.x += p.vx * dt;
+            p.loc.y += p.vy * dt;
+
+            let (sin, cos) = (p.spin * dt).sin_cos();
+            p.half = Point {
+                x: p.half.x * cos - p.half.y * sin,
+                y: p.half.y * cos + p.half.x * sin,
+            };
+
+            p.life > 0.0
+        });
+    }
+
+    /// Draws every particle, faded by how much of its life is left and moved
+    /// back towards where it was on the last tick like the asteroids.
+    pub fn draw(&self, lines: &mut LineHandler, alpha: f32) {
+        for p in &self.particles {
+            let loc = Point {
+                x: p.prev_loc.x + (p.loc.x - p.prev_loc.x) * alpha,
+                y: p.prev_loc.y + (p.loc.y - p.prev_loc.y) * alpha,
+            };
+            let fade = p.life / p.lifetime;
+            let color = p.color.map(|c| c * fade);
+
+            lines.add_line_colored(
+                Point {
+                    x: loc.x - p.half.x,
+                    y: loc.y - p.half.y,
+                },
+                Point {
+                    x: loc.x + p.half.x,
+                    y: loc.y + p.half.y,
+                },
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::WHITE;
+
+    fn pt(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    /// Edges of a 40 x 40 square around (100, 100).
+    fn square() -> Vec<(Point, Point)> {
+        let corners = [
+            pt(80.0, 80.0),
+            pt(120.0, 80.0),
+            pt(120.0, 120.0),
+            pt(80.0, 120.0),
+        ];
+        (0..4).map(|i| (corners[i], corners[(i + 1) % 4])).collect()
+    }
+
+    fn drawn(particles: &Particles) -> LineHandler {
+        let mut lines = LineHandler::new();
+        particles.draw(&mut lines, 1.0);
+        lines
+    }
+
+    #[test]
+    fn debris_breaks_each_edge_in_two() {
+        let mut particles = Particles::new(1);
+        particles.debris(pt(100.0, 100.0), &square(), 30.0, 0.0, WHITE);
+        assert_eq!(particles.particles.len(), 8);
+        for p in &particles.particles {
+            // half an edge long, starting out where that half of it was
+            assert!((p.half.x.hypot(p.half.y) - 10.0).abs() < 1e-4);
+            assert!((80.0..=120.0).contains(&p.loc.x) && (80.0..=120.0).contains(&p.loc.y));
+            // flying apart, on top of half the outline's own velocity
+            let (dx, dy) = (p.loc.x - 100.0, p.loc.y - 100.0);
+            assert!((p.vx - 15.0) * dx + p.vy * dy > 0.0);
+        }
+        assert_eq!(drawn(&particles).vertices.len(), 16);
+    }
+
+    #[test]
+    fn sparks_fan_out_around_their_direction() {
+        let mut particles = Particles::new(2);
+        particles.sparks(pt(500.0, 400.0), PI / 2.0, 12, WHITE);
+        assert_eq!(particles.particles.len(), 12);
+        for p in &particles.particles {
+            assert_eq!(p.loc, pt(500.0, 400.0));
+            let off = p.vy.atan2(p.vx) - PI / 2.0;
+            assert!(off.abs() <= PI / 3.0, "{}", off);
+        }
+    }
+
+    #[test]
+    fn particles_fade_and_expire() {
+        let mut particles = Particles::new(3);
+        particles.debris(pt(100.0, 100.0), &square(), 0.0, 0.0, WHITE);
+        particles.sparks(pt(500.0, 400.0), 0.0, 5, WHITE);
+        let brightness = |particles: &Particles| -> Vec<f32> {
+            drawn(particles)
+                .vertices
+                .iter()
+                .map(|v| v.color[0])
+                .collect()
+        };
+        assert!(brightness(&particles).iter().all(|&c| c == 1.0));
+
+        // shorter than any particle lives
+        particles.update(0.25);
+        assert_eq!(particles.particles.len(), 13);
+        assert!(brightness(&particles).iter().all(|&c| 0.0 < c && c < 1.0));
+
+        // sparks live at most 0.6 seconds, debris 1.2
+        particles.update(0.4);
+        assert!(particles.particles.len() <= 8);
+        for p in &particles.particles {
+            assert!((p.half.x.hypot(p.half.y) - 10.0).abs() < 1e-4);
+        }
+        particles.update(0.6);
+        assert!(particles.particles.is_empty());
+        assert!(drawn(&particles).vertices.is_empty());
+    }
+}
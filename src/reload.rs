@@ -0,0 +1,56 @@
+//! Watches the files the native game is tuned with, so edits to them apply
+//! while it's running.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    ffi::OsString,
+    path::Path,
+    sync::mpsc::{channel, Receiver},
+};
+
+pub struct FileWatcher {
+    // stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl FileWatcher {
+    /// Watches the files in `dir`. The directory is watched rather than each
+    /// file, as many editors save by replacing the file with a new one.
+    pub fn new(dir: impl AsRef<Path>) -> notify::Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // only fails once the receiver is dropped, when nobody cares
+            let _ = sender.send(event);
+        })?;
+        watcher.watch(dir.as_ref(), RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Names of the files created or written to since the last call, each
+    /// listed once however many times it changed.
+    pub fn changed(&self) -> Vec<OsString> {
+        let mut changed = Vec::new();
+        for event in self.events.try_iter() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("Couldn't watch for file changes: {}", e);
+                    continue;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for name in event.paths.iter().filter_map(|path| path.file_name()) {
+                if !changed.iter().any(|c| c == name) {
+                    changed.push(name.to_owned());
+                }
+            }
+        }
+        changed
+    }
+}
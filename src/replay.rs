@@ -0,0 +1,670 @@
+use std::{fmt, io};
+
+use crate::{
+    config::{AsteroidConfig, AsteroidsConfig, GameConfig, PlanetConfig},
+    game::{Game, TICK_RATE},
+    waves::{Mix, Pattern, Wave, Waves},
+};
+
+/// Identifies a replay file, followed by the format version.
+const MAGIC: &[u8; 4] = b"PDRP";
+const VERSION: u8 = 2;
+
+/// Longest replay that's loaded, a day of ticks. Playback runs every tick up
+/// to the end, so a corrupt end tick could otherwise keep it going for ages.
+pub const MAX_TICKS: u128 = 24 * 60 * 60 * TICK_RATE;
+
+const INPUT_CURSOR: u8 = 0;
+const INPUT_FIRE: u8 = 1;
+const INPUT_RESTART: u8 = 2;
+const INPUT_START: u8 = 3;
+const INPUT_RETURN_TO_TITLE: u8 = 4;
+
+/// A player input, mirroring the public input methods on [`Game`].
+///
+/// Pausing and resuming aren't among them: a paused game doesn't tick, so a
+/// run plays out the same as if the pause never happened, and inputs made
+/// while paused are recorded at the tick the game resumes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Cursor { x: u32, y: u32 },
+    Fire,
+    Restart,
+    Start,
+    ReturnToTitle,
+}
+
+impl Input {
+    pub fn apply(self, game: &mut Game) {
+        match self {
+            Input::Cursor { x, y } => game.set_cursor(x, y),
+            Input::Fire => game.fire(),
+            Input::Restart => game.restart(),
+            Input::Start => game.start(),
+            Input::ReturnToTitle => game.return_to_title(),
+        }
+    }
+}
+
+/// An input together with the number of ticks that had been simulated when
+/// it was made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub tick: u128,
+    pub input: Input,
+}
+
+/// Everything needed to reproduce a run: the seed it started from, the
+/// tuning it was played with, every input in order, and the tick the
+/// recording stopped at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    /// [`tuning_fingerprint`] of the config and waves it was recorded with.
+    pub tuning: u64,
+    pub events: Vec<Event>,
+    pub end_tick: u128,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownInput(u8),
+    Truncated,
+    Malformed,
+    /// The replay ends past [`MAX_TICKS`].
+    TooLong,
+    /// The events aren't in tick order, so can't be encoded as tick deltas.
+    OutOfOrder,
+    /// The replay was recorded with other tuning than it's being played with.
+    TuningMismatch,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "couldn't access replay file: {}", e),
+            ReplayError::BadMagic => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "unsupported replay version {}", v),
+            ReplayError::UnknownInput(kind) => write!(f, "unknown input kind {}", kind),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::Malformed => write!(f, "replay file is malformed"),
+            ReplayError::TooLong => write!(f, "replay is longer than a day"),
+            ReplayError::OutOfOrder => write!(f, "replay events are out of order"),
+            ReplayError::TuningMismatch => {
+                write!(f, "replay was recorded with a different config or waves")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, tuning: u64) -> Self {
+        Self {
+            seed,
+            tuning,
+            events: Vec::new(),
+            end_tick: 0,
+        }
+    }
+
+    /// Encodes the replay as the magic, version byte, and little-endian seed
+    /// and tuning fingerprint, followed by LEB128 varints for the end tick,
+    /// the event count and each event's tick delta and payload. Fails if the
+    /// events aren't in tick order.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayError> {
+        let mut bytes = Vec::with_capacity(16 + self.events.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.tuning.to_le_bytes());
+        write_varint(&mut bytes, self.end_tick);
+        write_varint(&mut bytes, self.events.len() as u128);
+
+        let mut last_tick = 0;
+        for event in &self.events {
+            let delta = event
+                .tick
+                .checked_sub(last_tick)
+                .ok_or(ReplayError::OutOfOrder)?;
+            write_varint(&mut bytes, delta);
+            last_tick = event.tick;
+
+            match event.input {
+                Input::Cursor { x, y } => {
+                    bytes.push(INPUT_CURSOR);
+                    write_varint(&mut bytes, x as u128);
+                    write_varint(&mut bytes, y as u128);
+                }
+                Input::Fire => bytes.push(INPUT_FIRE),
+                Input::Restart => bytes.push(INPUT_RESTART),
+                Input::Start => bytes.push(INPUT_START),
+                Input::ReturnToTitle => bytes.push(INPUT_RETURN_TO_TITLE),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = reader.u64()?;
+        let tuning = reader.u64()?;
+        let end_tick = reader.varint()?;
+        if end_tick > MAX_TICKS {
+            return Err(ReplayError::TooLong);
+        }
+        let count = reader.varint()?;
+
+        let mut events = Vec::new();
+        let mut tick = 0;
+        for _ in 0..count {
+            tick = reader
+                .varint()?
+                .checked_add(tick)
+                .filter(|&tick| tick <= end_tick)
+                .ok_or(ReplayError::Malformed)?;
+            let input = match reader.byte()? {
+                INPUT_CURSOR => Input::Cursor {
+                    x: reader.u32()?,
+                    y: reader.u32()?,
+                },
+                INPUT_FIRE => Input::Fire,
+                INPUT_RESTART => Input::Restart,
+                INPUT_START => Input::Start,
+                INPUT_RETURN_TO_TITLE => Input::ReturnToTitle,
+                kind => return Err(ReplayError::UnknownInput(kind)),
+            };
+            events.push(Event { tick, input });
+        }
+        if !reader.at_end() {
+            return Err(ReplayError::Malformed);
+        }
+
+        Ok(Self {
+            seed,
+            tuning,
+            events,
+            end_tick,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), ReplayError> {
+        Ok(std::fs::write(path, self.to_bytes()?)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ReplayError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Plays the whole replay into a fresh game with the built in config and
+    /// waves, and returns it in its final state.
+    pub fn play(&self) -> Result<Game, ReplayError> {
+        self.play_with(GameConfig::default(), Waves::default())
+    }
+
+    /// Like [`Replay::play`], with the config and waves the replay was
+    /// recorded with.
+    pub fn play_with(&self, config: GameConfig, waves: Waves) -> Result<Game, ReplayError> {
+        if tuning_fingerprint(&config, &waves) != self.tuning {
+            return Err(ReplayError::TuningMismatch);
+        }
+        let mut game = Game::with_tuning(self.seed, config, waves);
+        let mut player = Player::new(self);
+        while !player.is_finished(&game) {
+            player.advance(&mut game);
+        }
+        player.finish(&mut game);
+        Ok(game)
+    }
+}
+
+/// Feeds a replay back into a game one tick at a time, so it can also be
+/// watched as it plays.
+pub struct Player<'a> {
+    replay: &'a Replay,
+    next_event: usize,
+}
+
+impl<'a> Player<'a> {
+    pub fn new(replay: &'a Replay) -> Self {
+        Self {
+            replay,
+            next_event: 0,
+        }
+    }
+
+    pub fn is_finished(&self, game: &Game) -> bool {
+        game.current_tick() >= self.replay.end_tick
+    }
+
+    /// Applies the inputs recorded before the game's next tick, then runs it.
+    /// `game` must be a fresh game created with `Game::with_tuning` from the
+    /// replay's seed and the tuning it was recorded with.
+    pub fn advance(&mut self, game: &mut Game) {
+        self.apply_inputs(game);
+        game.tick();
+    }
+
+    /// Applies the inputs made after the last tick, as there's no tick left
+    /// to apply them before. Call once [`Player::is_finished`] says so.
+    pub fn finish(&mut self, game: &mut Game) {
+        self.apply_inputs(game);
+    }
+
+    fn apply_inputs(&mut self, game: &mut Game) {
+        while let Some(event) = self.replay.events.get(self.next_event) {
+            if event.tick > game.current_tick() {
+                break;
+            }
+            event.input.apply(game);
+            self.next_event += 1;
+        }
+    }
+}
+
+/// Hash of everything a game is tuned with, telling apart replays that can
+/// only be played back with different tuning. Every field is fed in a fixed
+/// order, floats by their bits, so it only changes when the tuning does.
+pub fn tuning_fingerprint(config: &GameConfig, waves: &Waves) -> u64 {
+    let mut hash = Fingerprint::new();
+
+    // spelled out in full so new fields can't be left out
+    let GameConfig {
+        lives,
+        ship_distance,
+        ship_length,
+        laser_speed,
+        laser_length,
+        spawn_distance,
+        split_angle,
+        planet,
+        asteroids,
+    } = config;
+    let PlanetConfig { radius, pulse } = planet;
+    let AsteroidsConfig {
+        large,
+        medium,
+        small,
+    } = asteroids;
+    hash.u32(*lives);
+    for value in [
+        ship_distance,
+        ship_length,
+        laser_speed,
+        laser_length,
+        spawn_distance,
+        split_angle,
+        radius,
+        pulse,
+    ] {
+        hash.f32(*value);
+    }
+    for AsteroidConfig {
+        radius,
+        speed,
+        score,
+    } in [large, medium, small]
+    {
+        hash.f32(*radius);
+        hash.f32(*speed);
+        hash.u32(*score);
+    }
+
+    let Waves { escalation, waves } = waves;
+    hash.f32(*escalation);
+    hash.u64(waves.len() as u64);
+    for Wave {
+        asteroids,
+        mix: Mix {
+            large,
+            medium,
+            small,
+        },
+        speed: (slowest, fastest),
+        spawn_interval,
+        pattern,
+        group,
+        intermission,
+    } in waves
+    {
+        hash.u32(*asteroids);
+        for value in [large, medium, small, slowest, fastest, spawn_interval] {
+            hash.f32(*value);
+        }
+        hash.u32(match pattern {
+            Pattern::Random => 0,
+            Pattern::Sweep => 1,
+            Pattern::Ring => 2,
+        });
+        hash.u32(*group);
+        hash.f32(*intermission);
+    }
+
+    hash.0
+}
+
+/// FNV-1a over little-endian values.
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReplayError> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(ReplayError::Truncated)?;
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        u32::try_from(self.varint()?).map_err(|_| ReplayError::Malformed)
+    }
+
+    fn varint(&mut self) -> Result<u128, ReplayError> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as u128;
+            // too long for a u128, or with bits that would be shifted out
+            if shift >= 128 || bits.leading_zeros() < shift {
+                return Err(ReplayError::Malformed);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    fn replay() -> Replay {
+        Replay {
+            seed: 0x0123_4567_89ab_cdef,
+            tuning: tuning_fingerprint(&GameConfig::default(), &Waves::default()),
+            events: vec![
+                Event {
+                    tick: 0,
+                    input: Input::Start,
+                },
+                Event {
+                    tick: 3,
+                    input: Input::Cursor { x: 700, y: 300 },
+                },
+                Event {
+                    tick: 3,
+                    input: Input::Fire,
+                },
+                Event {
+                    tick: 200,
+                    input: Input::Restart,
+                },
+                Event {
+                    tick: 100_000,
+                    input: Input::ReturnToTitle,
+                },
+            ],
+            end_tick: 100_001,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let replay = replay();
+        assert_eq!(
+            Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap(),
+            replay
+        );
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let bytes = replay().to_bytes().unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            Replay::from_bytes(&bad_magic),
+            Err(ReplayError::BadMagic)
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[MAGIC.len()] = VERSION + 1;
+        assert!(matches!(
+            Replay::from_bytes(&bad_version),
+            Err(ReplayError::UnsupportedVersion(v)) if v == VERSION + 1
+        ));
+
+        // magic, version, seed, tuning and the three byte end tick, followed
+        // by a single byte event count and tick delta before the start input
+        let header = MAGIC.len() + 1 + 16 + 3;
+        let mut unknown_input = bytes.clone();
+        unknown_input[header + 2] = 99;
+        assert!(matches!(
+            Replay::from_bytes(&unknown_input),
+            Err(ReplayError::UnknownInput(99))
+        ));
+
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    Replay::from_bytes(&bytes[..len]),
+                    Err(ReplayError::Truncated)
+                ),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn only_encodes_events_in_order() {
+        let mut replay = replay();
+        replay.events.swap(2, 3);
+        assert!(matches!(replay.to_bytes(), Err(ReplayError::OutOfOrder)));
+    }
+
+    #[test]
+    fn rejects_cursor_out_of_range() {
+        let mut replay = replay();
+        replay.events.truncate(1);
+        let mut bytes = replay.to_bytes().unwrap();
+        bytes.extend_from_slice(&[0, INPUT_CURSOR]);
+        write_varint(&mut bytes, u32::MAX as u128 + 1);
+        write_varint(&mut bytes, 0);
+        // counting the extra event, which follows the header as above
+        bytes[MAGIC.len() + 1 + 16 + 3] = 2;
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let bytes = replay().to_bytes().unwrap();
+        let header = MAGIC.len() + 1 + 16;
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Replay::from_bytes(&trailing),
+            Err(ReplayError::Malformed)
+        ));
+
+        // an end tick of 2^128, one bit more than fits
+        let mut too_long = bytes[..header].to_vec();
+        too_long.extend_from_slice(&[0x80; 18]);
+        too_long.extend_from_slice(&[0x04, 0]);
+        assert!(matches!(
+            Replay::from_bytes(&too_long),
+            Err(ReplayError::Malformed)
+        ));
+
+        let mut past_a_day = replay();
+        past_a_day.end_tick = MAX_TICKS + 1;
+        assert!(matches!(
+            Replay::from_bytes(&past_a_day.to_bytes().unwrap()),
+            Err(ReplayError::TooLong)
+        ));
+        let mut a_day = replay();
+        a_day.end_tick = MAX_TICKS;
+        assert_eq!(
+            Replay::from_bytes(&a_day.to_bytes().unwrap()).unwrap(),
+            a_day
+        );
+
+        let mut past_the_end = replay();
+        past_the_end.end_tick = 99_999;
+        assert!(matches!(
+            Replay::from_bytes(&past_the_end.to_bytes().unwrap()),
+            Err(ReplayError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn fingerprint_follows_the_tuning() {
+        let config = GameConfig::default();
+        let waves = Waves::default();
+        let fingerprint = tuning_fingerprint(&config, &waves);
+
+        let mut faster = config.clone();
+        faster.asteroids.small.speed += 0.1;
+        assert_ne!(tuning_fingerprint(&faster, &waves), fingerprint);
+
+        let mut sweeping = waves.clone();
+        sweeping.waves[0].pattern = Pattern::Sweep;
+        assert_ne!(tuning_fingerprint(&config, &sweeping), fingerprint);
+    }
+
+    #[test]
+    fn playing_back_matches_the_recorded_run() {
+        let mut game = Game::with_seed(7);
+        game.start_recording();
+        game.start();
+        let mut ms = 0;
+        while game.state() == GameState::Playing {
+            let angle = ms as f32 / 500.0;
+            game.set_cursor(
+                (500.0 + 300.0 * angle.cos()) as u32,
+                (500.0 + 300.0 * angle.sin()) as u32,
+            );
+            if ms % 150 == 0 {
+                game.fire();
+            }
+            // uneven frames, which playback doesn't have
+            game.step(7 + ms as u128 % 13);
+            ms += 10;
+        }
+        game.step(500);
+        let replay = game.take_replay().unwrap();
+
+        let replay = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+        let played = replay.play().unwrap();
+        assert_eq!(played.snapshot(), game.snapshot());
+    }
+
+    #[test]
+    fn inputs_after_the_last_tick_are_played_back() {
+        let mut game = Game::with_seed(8);
+        game.start_recording();
+        game.start();
+        while game.state() == GameState::Playing {
+            game.step(100);
+        }
+        // made after the last tick, so recorded on the end tick
+        game.restart();
+        game.set_cursor(100, 900);
+        game.fire();
+        let replay = game.take_replay().unwrap();
+        assert_eq!(replay.events.last().unwrap().tick, replay.end_tick);
+
+        let replay = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+        let played = replay.play().unwrap();
+        assert_eq!(played.state(), GameState::Playing);
+        assert_eq!(played.snapshot(), game.snapshot());
+    }
+}
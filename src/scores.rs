@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, io};
+
+/// Number of runs the high-score table keeps.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Number of letters a high score is signed with.
+pub const INITIALS_LEN: usize = 3;
+
+/// Key the table is kept under in the browser's `localStorage`.
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "planet-defender-high-scores";
+
+/// A finished run in the high-score table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub score: u32,
+    /// How long the run survived, in milliseconds.
+    pub time: u64,
+    /// When the run ended, in seconds since the Unix epoch.
+    pub date: u64,
+    /// Seed the run was played with.
+    #[serde(with = "hex")]
+    pub seed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initials: Option<String>,
+}
+
+/// The best runs so far, highest score first, kept between sessions in a
+/// file in the platform's data directory on native and in `localStorage` on
+/// the web.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighScores {
+    #[serde(rename = "run", default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+pub enum ScoresError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    /// Neither a data directory nor `localStorage` could be found.
+    NoStorage,
+}
+
+impl fmt::Display for ScoresError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoresError::Io(e) => write!(f, "couldn't access high scores: {}", e),
+            ScoresError::Parse(e) => write!(f, "couldn't parse high scores: {}", e),
+            ScoresError::Serialize(e) => write!(f, "couldn't serialize high scores: {}", e),
+            ScoresError::NoStorage => write!(f, "nowhere to keep high scores"),
+        }
+    }
+}
+
+impl std::error::Error for ScoresError {}
+
+impl From<io::Error> for ScoresError {
+    fn from(e: io::Error) -> Self {
+        ScoresError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ScoresError {
+    fn from(e: toml::de::Error) -> Self {
+        ScoresError::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for ScoresError {
+    fn from(e: toml::ser::Error) -> Self {
+        ScoresError::Serialize(e)
+    }
+}
+
+impl HighScores {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Whether a run scoring `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds `entry` below any runs with the same score, dropping the lowest
+    /// run if the table is full. Returns where it ended up, or `None` if it
+    /// didn't make the table.
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    /// Signs the run at `rank` with `initials`.
+    pub fn sign(&mut self, rank: usize, initials: String) {
+        if let Some(entry) = self.entries.get_mut(rank) {
+            entry.initials = Some(initials);
+        }
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ScoresError> {
+        let mut scores: HighScores = toml::from_str(source)?;
+        // in case the file was edited by hand
+        scores.entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        scores.entries.truncate(MAX_ENTRIES);
+        Ok(scores)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ScoresError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Reads the table kept from earlier sessions, which is empty if there
+    /// were none.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Result<Self, ScoresError> {
+        let path = scores_path().ok_or(ScoresError::NoStorage)?;
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::from_toml(&source),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Keeps the table for later sessions.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) -> Result<(), ScoresError> {
+        let path = scores_path().ok_or(ScoresError::NoStorage)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(std::fs::write(path, self.to_toml()?)?)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Result<Self, ScoresError> {
+        match local_storage()?.get_item(STORAGE_KEY) {
+            Ok(Some(source)) => Self::from_toml(&source),
+            Ok(None) => Ok(Self::default()),
+            Err(_) => Err(ScoresError::NoStorage),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) -> Result<(), ScoresError> {
+        local_storage()?
+            .set_item(STORAGE_KEY, &self.to_toml()?)
+            .map_err(|_| ScoresError::NoStorage)
+    }
+}
+
+/// Arcade-style entry of the initials a new high score is signed with: one
+/// letter at a time, each picked by typing it or by cycling through the
+/// alphabet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitialsEntry {
+    letters: [u8; INITIALS_LEN],
+    cursor: usize,
+}
+
+impl InitialsEntry {
+    pub fn new() -> Self {
+        Self {
+            letters: [b'A'; INITIALS_LEN],
+            cursor: 0,
+        }
+    }
+
+    /// The letters entered so far, with `A` for any not picked yet.
+    pub fn text(&self) -> String {
+        self.letters.iter().map(|&l| l as char).collect()
+    }
+
+    /// Index of the letter being picked.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves the letter being picked `steps` through the alphabet, wrapping
+    /// round from Z to A and back.
+    pub fn cycle(&mut self, steps: i32) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = b'A' + (*letter as i32 - b'A' as i32 + steps).rem_euclid(26) as u8;
+    }
+
+    /// Picks `c` for the current letter and moves on to the next, ignoring
+    /// anything but the letters of the alphabet.
+    pub fn type_letter(&mut self, c: char) {
+        if c.is_ascii_alphabetic() {
+            self.letters[self.cursor] = c.to_ascii_uppercase() as u8;
+            self.move_cursor(1);
+        }
+    }
+
+    /// Moves `steps` letters forwards or backwards, stopping at either end.
+    pub fn move_cursor(&mut self, steps: i32) {
+        self.cursor = (self.cursor as i32 + steps).clamp(0, INITIALS_LEN as i32 - 1) as usize;
+    }
+}
+
+impl Default for InitialsEntry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the high scores are kept on native, in the data directory each
+/// platform conventionally keeps per-user application data in.
+#[cfg(not(target_arch = "wasm32"))]
+fn scores_path() -> Option<std::path::PathBuf> {
+    use std::{env, path::PathBuf};
+
+    let non_empty = |var: &str| {
+        env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let data_dir = if cfg!(target_os = "windows") {
+        non_empty("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        non_empty("XDG_DATA_HOME")
+            .or_else(|| non_empty("HOME").map(|home| home.join(".local/share")))
+    };
+    Some(data_dir?.join("planet-defender").join("high_scores.toml"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, ScoresError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or(ScoresError::NoStorage)
+}
+
+/// The current time in seconds since the Unix epoch, for dating entries.
+pub fn now() -> u64 {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            (js_sys::Date::now() / 1000.0) as u64
+        } else {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        }
+    }
+}
+
+/// Formats `date`, in seconds since the Unix epoch, as a UTC `YYYY-MM-DD`.
+pub fn format_date(date: u64) -> String {
+    // Howard Hinnant's days to civil date algorithm
+    let days = (date / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Seeds use the whole range of `u64`, which TOML integers can't hold, so
+/// they're kept as hex strings.
+mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:016x}", seed))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        u64::from_str_radix(&hex, 16).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> Entry {
+        Entry {
+            score,
+            time: 1000,
+            date: 0,
+            seed: u64::MAX,
+            initials: None,
+        }
+    }
+
+    #[test]
+    fn keeps_best_runs_in_order() {
+        let mut scores = HighScores::default();
+        for score in 1..=MAX_ENTRIES as u32 {
+            scores.insert(entry(score));
+        }
+        assert!(!scores.qualifies(1));
+        assert_eq!(scores.insert(entry(1)), None);
+
+        // below the earlier run with the same score
+        assert_eq!(scores.insert(entry(5)), Some(6));
+        assert_eq!(scores.entries().len(), MAX_ENTRIES);
+        assert_eq!(scores.entries()[0].score, MAX_ENTRIES as u32);
+        assert_eq!(scores.entries().last().unwrap().score, 2);
+    }
+
+    #[test]
+    fn empty_runs_never_qualify() {
+        assert!(!HighScores::default().qualifies(0));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let mut scores = HighScores::default();
+        scores.insert(entry(3));
+        scores.insert(Entry {
+            initials: Some("ABC".to_string()),
+            ..entry(7)
+        });
+        let loaded = HighScores::from_toml(&scores.to_toml().unwrap()).unwrap();
+        assert_eq!(loaded, scores);
+    }
+
+    #[test]
+    fn enters_initials() {
+        let mut initials = InitialsEntry::new();
+        initials.type_letter('z');
+        initials.type_letter('7');
+        initials.cycle(-1);
+        initials.move_cursor(1);
+        initials.cycle(28);
+        // typing on the last letter stays there
+        initials.type_letter('x');
+        initials.type_letter('y');
+        assert_eq!(initials.text(), "ZZY");
+
+        initials.move_cursor(-5);
+        assert_eq!(initials.cursor(), 0);
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_281_600), "2026-10-18");
+    }
+}
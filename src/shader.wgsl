@@ -3,17 +3,20 @@
 struct CameraUniform {
     view_proj: mat4x4<f32>,
 };
-@group(1) @binding(0) // 1.
+@group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>,
+    // signed distance from the centre of the line, half width of the line
+    @location(2) edge: vec2<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec3<f32>,
+    @location(1) edge: vec2<f32>,
 };
 
 @vertex
@@ -22,7 +25,8 @@ fn vs_main(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.color = model.color;
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.edge = model.edge;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
     return out;
 }
 
@@ -30,5 +34,7 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    // fade out over the last pixel past the edge of the line
+    let alpha = clamp(in.edge.y + 0.5 - abs(in.edge.x), 0.0, 1.0);
+    return vec4<f32>(in.color, alpha);
 }
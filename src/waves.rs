@@ -0,0 +1,333 @@
+use serde::Deserialize;
+use std::{fmt, io};
+
+/// The waves the game ships with, also used on the web where there's no file
+/// system to load them from.
+pub const DEFAULT_WAVES: &str = include_str!("../assets/waves.toml");
+
+/// Where the native game looks for waves to play, relative to the working
+/// directory; see `assets/waves.toml` for the format.
+#[cfg(not(target_arch = "wasm32"))]
+pub const WAVES_PATH: &str = "assets/waves.toml";
+
+/// Most the last wave is scaled by however often it's repeated, far past
+/// anything playable but keeping late waves' numbers finite.
+const MAX_ESCALATION: f32 = 1000.0;
+
+/// Fastest asteroids are allowed to come in, in pixels per second, crossing
+/// the playfield in a hundredth of a second.
+const MAX_SPEED: f32 = 100_000.0;
+
+/// Relative chance of each asteroid size turning up in a wave.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Mix {
+    pub large: f32,
+    pub medium: f32,
+    pub small: f32,
+}
+
+/// Where the asteroids of a wave come from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Pattern {
+    /// Anywhere around the planet.
+    #[default]
+    Random,
+    /// A little further round than the one before.
+    Sweep,
+    /// Each group spread evenly around the planet.
+    Ring,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Wave {
+    /// Number of asteroids spawned, not counting the fragments they split into.
+    pub asteroids: u32,
+    pub mix: Mix,
+    /// Slowest and fastest speed in pixels per second, before scaling by size.
+    pub speed: (f32, f32),
+    /// Seconds between spawns.
+    pub spawn_interval: f32,
+    #[serde(default)]
+    pub pattern: Pattern,
+    /// Number of asteroids spawned at once.
+    #[serde(default = "default_group")]
+    pub group: u32,
+    /// Seconds of quiet before the wave starts, while its banner shows.
+    #[serde(default)]
+    pub intermission: f32,
+}
+
+fn default_group() -> u32 {
+    1
+}
+
+/// Every wave of a game, in the order they're played.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Waves {
+    /// How much the asteroid count and speed of the last wave grow every
+    /// time it's repeated.
+    #[serde(default = "default_escalation")]
+    pub escalation: f32,
+    #[serde(rename = "wave")]
+    pub waves: Vec<Wave>,
+}
+
+fn default_escalation() -> f32 {
+    1.0
+}
+
+#[derive(Debug)]
+pub enum WavesError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// The file parsed but describes waves that can't be played.
+    Invalid(String),
+}
+
+impl fmt::Display for WavesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavesError::Io(e) => write!(f, "couldn't read waves file: {}", e),
+            WavesError::Parse(e) => write!(f, "couldn't parse waves: {}", e),
+            WavesError::Invalid(reason) => write!(f, "invalid waves: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for WavesError {}
+
+impl From<io::Error> for WavesError {
+    fn from(e: io::Error) -> Self {
+        WavesError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for WavesError {
+    fn from(e: toml::de::Error) -> Self {
+        WavesError::Parse(e)
+    }
+}
+
+impl Waves {
+    pub fn from_toml(source: &str) -> Result<Self, WavesError> {
+        let waves: Waves = toml::from_str(source)?;
+        waves.validate()?;
+        Ok(waves)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, WavesError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    fn validate(&self) -> Result<(), WavesError> {
+        let invalid = |reason: String| Err(WavesError::Invalid(reason));
+
+        if self.waves.is_empty() {
+            return invalid("there are no waves".to_string());
+        }
+        // below 1 the repeated waves shrink to nothing, and as empty waves
+        // are over as soon as they start the run would never end
+        if !(self.escalation.is_finite() && self.escalation >= 1.0) {
+            return invalid(format!(
+                "escalation must be at least 1, not {}",
+                self.escalation
+            ));
+        }
+
+        for (i, wave) in self.waves.iter().enumerate() {
+            let number = i + 1;
+            let (slowest, fastest) = wave.speed;
+            let Mix {
+                large,
+                medium,
+                small,
+            } = wave.mix;
+
+            if wave.asteroids == 0 {
+                return invalid(format!("wave {} has no asteroids", number));
+            }
+            // asteroids that never move would never arrive, stalling the run
+            if !(slowest.is_finite()
+                && fastest.is_finite()
+                && 0.0 <= slowest
+                && slowest <= fastest
+                && fastest > 0.0
+                && fastest <= MAX_SPEED)
+            {
+                return invalid(format!("wave {} has an invalid speed range", number));
+            }
+            if !(wave.spawn_interval.is_finite() && wave.spawn_interval >= 0.0) {
+                return invalid(format!("wave {} has an invalid spawn interval", number));
+            }
+            if !(wave.intermission.is_finite() && wave.intermission >= 0.0) {
+                return invalid(format!("wave {} has an invalid intermission", number));
+            }
+            if wave.group == 0 {
+                return invalid(format!("wave {} spawns groups of 0", number));
+            }
+            let weights = [large, medium, small];
+            if weights.iter().any(|w| !(w.is_finite() && *w >= 0.0))
+                || weights.iter().sum::<f32>() <= 0.0
+            {
+                return invalid(format!("wave {} has no asteroid sizes in its mix", number));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The wave played `number`th, counting from 0. Past the last wave that
+    /// one is repeated, escalating every time.
+    pub fn wave(&self, number: usize) -> Wave {
+        let last = self.waves.len() - 1;
+        let mut wave = self.waves[number.min(last)].clone();
+
+        let repeats = number.saturating_sub(last);
+        if repeats > 0 {
+            let factor = self
+                .escalation
+                .powi(repeats.min(i32::MAX as usize) as i32)
+                .min(MAX_ESCALATION);
+            // casting saturates
+            wave.asteroids = (wave.asteroids as f32 * factor).round() as u32;
+            let scale = |speed: f32| (speed * factor).min(MAX_SPEED);
+            wave.speed = (scale(wave.speed.0), scale(wave.speed.1));
+        }
+        wave
+    }
+}
+
+impl Default for Waves {
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_WAVES).expect("the built in waves are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn built_in_waves_are_valid() {
+        Waves::from_toml(DEFAULT_WAVES).unwrap();
+    }
+
+    #[test]
+    fn last_wave_repeats_and_escalates() {
+        let waves = Waves::from_toml(
+            r#"
+            escalation = 2.0
+
+            [[wave]]
+            asteroids = 1
+            mix = { small = 1 }
+            speed = [10.0, 20.0]
+            spawn_interval = 1.0
+
+            [[wave]]
+            asteroids = 3
+            mix = { large = 1 }
+            speed = [10.0, 20.0]
+            spawn_interval = 1.0
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(waves.wave(0).asteroids, 1);
+        assert_eq!(waves.wave(1).asteroids, 3);
+        assert_eq!(waves.wave(3).asteroids, 12);
+        assert_eq!(waves.wave(3).speed, (40.0, 80.0));
+    }
+
+    #[test]
+    fn escalation_stays_finite() {
+        let waves = Waves::from_toml(
+            r#"
+            escalation = 1.5
+
+            [[wave]]
+            asteroids = 10
+            mix = { small = 1 }
+            speed = [10.0, 200.0]
+            spawn_interval = 1.0
+            "#,
+        )
+        .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for number in [500, 100_000, usize::MAX] {
+            let wave = waves.wave(number);
+            assert_eq!(wave.asteroids, 10_000);
+            assert_eq!(wave.speed, (10_000.0, MAX_SPEED));
+            // as asteroids are spawned
+            rng.gen_range(wave.speed.0..=wave.speed.1);
+        }
+    }
+
+    #[test]
+    fn rejects_unplayable_waves() {
+        let result = Waves::from_toml(
+            r#"
+            [[wave]]
+            asteroids = 1
+            mix = {}
+            speed = [20.0, 10.0]
+            spawn_interval = 1.0
+            "#,
+        );
+        assert!(matches!(result, Err(WavesError::Invalid(_))));
+        let result = Waves::from_toml(
+            r#"
+            [[wave]]
+            asteroids = 1
+            mix = { small = 1 }
+            speed = [0.0, 0.0]
+            spawn_interval = 1.0
+            "#,
+        );
+        assert!(matches!(result, Err(WavesError::Invalid(_))));
+        let result = Waves::from_toml(
+            r#"
+            [[wave]]
+            asteroids = 0
+            mix = { small = 1 }
+            speed = [10.0, 20.0]
+            spawn_interval = 1.0
+            "#,
+        );
+        assert!(matches!(result, Err(WavesError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_shrinking_escalation() {
+        let waves = |escalation| {
+            Waves::from_toml(&format!(
+                r#"
+                escalation = {:?}
+
+                [[wave]]
+                asteroids = 2
+                mix = {{ small = 1 }}
+                speed = [10.0, 20.0]
+                spawn_interval = 1.0
+                "#,
+                escalation
+            ))
+        };
+        for escalation in [0.5, 0.999, 0.0, -1.0] {
+            assert!(
+                matches!(waves(escalation), Err(WavesError::Invalid(_))),
+                "{}",
+                escalation
+            );
+        }
+        // repeating the last wave unchanged is fine
+        assert_eq!(waves(1.0).unwrap().wave(100).asteroids, 2);
+    }
+}